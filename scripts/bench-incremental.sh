@@ -0,0 +1,57 @@
+#!/usr/bin/env bash
+# Times a full run against a no-change --incremental rerun on a generated repository, once with plain files
+# (reading and writing the document dominate) and once with a --filter-cmd (per-file processing dominates).
+# Usage: scripts/bench-incremental.sh [files] [runs]   (defaults: 5000 files, 5 runs each)
+set -euo pipefail
+
+files=${1:-5000}
+runs=${2:-5}
+root=$(cd "$(dirname "$0")/.." && pwd)
+work=$(mktemp -d)
+trap 'rm -rf "$work"' EXIT
+
+cargo build --release --quiet --manifest-path "$root/Cargo.toml"
+bin="$root/target/release/repo-to-ai-context-docs"
+
+# A repository of source files of about 8 KB (200 lines) spread over 50 directories
+mkdir -p "$work/repo"
+template="$work/template.rs"
+for ((line = 0; line < 200; line++)); do
+    printf 'pub fn function_NAME_%d(value: u64) -> u64 { value.wrapping_mul(%d).rotate_left(3) }\n' "$line" "$line"
+done > "$template"
+for ((i = 0; i < files; i++)); do
+    dir="$work/repo/src/module$((i % 50))"
+    mkdir -p "$dir"
+    sed "s/NAME/$i/" "$template" > "$dir/file$i.rs"
+done
+git -C "$work/repo" init -q
+git -C "$work/repo" add -A
+git -C "$work/repo" -c user.name=bench -c user.email=bench@example.com commit -qm bench
+
+cd "$work"
+# Median wall-clock time of a command over $runs runs, in milliseconds
+median() {
+    local times=()
+    for ((r = 0; r < runs; r++)); do
+        local start end
+        start=$(date +%s%N)
+        "$@" > /dev/null
+        end=$(date +%s%N)
+        times+=($(((end - start) / 1000000)))
+    done
+    printf '%s\n' "${times[@]}" | sort -n | sed -n "$(((runs + 1) / 2))p"
+}
+
+# Compares a full run with a rerun reusing every section, for the given extra options
+compare() {
+    local full incremental
+    full=$(median "$bin" repo -q "$@")
+    "$bin" repo -q --incremental "$@" # writes the manifest the reruns start from
+    incremental=$(median "$bin" repo -q --incremental "$@")
+    awk -v f="$full" -v i="$incremental" -v name="$*" \
+        'BEGIN { printf "%-28s full %6d ms   incremental %6d ms   %.1fx\n", name == "" ? "(defaults)" : name, f, i, f / (i > 0 ? i : 1) }'
+}
+
+echo "$files files of about 8 KB, median of $runs runs"
+compare
+compare --filter-cmd cat
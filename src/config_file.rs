@@ -0,0 +1,75 @@
+//! Options file (--config): TOML settings that are tedious to repeat on the command line.
+//! Holds the `[limits]` table, the file form of repeatable `--limit` rules:
+//!
+//! ```toml
+//! [limits]
+//! "**/*.json" = "10K"
+//! "**/*.snap" = "50lines"
+//! ```
+
+use std::fs;
+use std::path::Path;
+use toml::{Table, Value};
+
+/// Settings read from an options file
+#[derive(Debug, Default)]
+pub struct ConfigFile {
+    pub limits: Vec<String>, // `--limit` rules (`<pattern>=<limit>`), in file order
+}
+
+impl ConfigFile {
+    /// Reads and validates an options file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Unable to read config file '{}': {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("Invalid config file '{}': {}", path.display(), e))
+    }
+
+    /// Parses the TOML text of an options file
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: Table = text.parse().map_err(|e: toml::de::Error| e.message().to_string())?;
+        let mut config = Self::default();
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("limits", Value::Table(limits)) => {
+                    for (pattern, limit) in limits {
+                        let limit = match limit {
+                            Value::String(limit) => limit,
+                            Value::Integer(bytes) if bytes >= 0 => bytes.to_string(),
+                            _ => return Err(format!("limit of '{}' must be a size or line count such as \"10K\" or \"50lines\"", pattern)),
+                        };
+                        config.limits.push(format!("{}={}", pattern, limit));
+                    }
+                }
+                ("limits", _) => return Err("'limits' must be a table".to_string()),
+                (key, _) => return Err(format!("unknown key '{}' (expected [limits])", key)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_keep_their_order() {
+        let config = ConfigFile::parse("[limits]\n\"**/*.snap\" = \"50lines\"\n\"**/*.json\" = \"10K\"\n\"*.bin\" = 4096\n").unwrap();
+        assert_eq!(config.limits, ["**/*.snap=50lines", "**/*.json=10K", "*.bin=4096"]);
+    }
+
+    #[test]
+    fn empty_files_have_no_limits() {
+        assert!(ConfigFile::parse("").unwrap().limits.is_empty());
+        assert!(ConfigFile::parse("[limits]\n").unwrap().limits.is_empty());
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        assert!(ConfigFile::parse("[limits\n").is_err());
+        assert!(ConfigFile::parse("limits = 5\n").unwrap_err().contains("must be a table"));
+        assert!(ConfigFile::parse("[limit]\n").unwrap_err().contains("unknown key 'limit'"));
+        assert!(ConfigFile::parse("[limits]\n\"*.json\" = true\n").is_err());
+        assert!(ConfigFile::parse("[limits]\n\"*.json\" = -1\n").is_err());
+    }
+}
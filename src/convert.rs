@@ -0,0 +1,172 @@
+//! Converters that turn file formats which are noisy when included verbatim into readable text.
+//! A converter is picked by file extension; when it fails, the file is included as it is.
+
+use serde_json::Value;
+
+/// Converts the contents of files with certain extensions before they are rendered
+pub trait Converter: Sync {
+    /// Lowercase extensions (without the dot) this converter handles
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Converts a file's text; errors describe why the original text has to be kept
+    fn convert(&self, text: &str) -> Result<Conversion, String>;
+}
+
+/// Converted file contents
+#[derive(Debug)]
+pub struct Conversion {
+    pub text: String,
+    pub language: &'static str, // Language of the converted text, for the code fence
+}
+
+/// All converters, checked in order
+static CONVERTERS: [&dyn Converter; 1] = [&Notebook];
+
+/// The converter for a file name, if any
+pub fn for_file(name: &str) -> Option<&'static dyn Converter> {
+    let (_, ext) = name.rsplit_once('.')?;
+    let ext = ext.to_lowercase();
+    CONVERTERS.iter().copied().find(|converter| converter.extensions().contains(&ext.as_str()))
+}
+
+/// Shortest backtick fence (at least three) that no backtick run in `text` can close
+pub fn fence(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// Jupyter notebooks (.ipynb): markdown cells as markdown, code cells as fenced code, outputs summarized
+struct Notebook;
+
+impl Converter for Notebook {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["ipynb"]
+    }
+
+    fn convert(&self, text: &str) -> Result<Conversion, String> {
+        let notebook: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let cells = notebook.get("cells").and_then(Value::as_array).ok_or("no cells list")?;
+        let metadata = notebook.get("metadata");
+        let language = metadata
+            .and_then(|metadata| metadata.pointer("/language_info/name").or_else(|| metadata.pointer("/kernelspec/language")))
+            .and_then(Value::as_str)
+            .unwrap_or("python");
+        let kernel = metadata.and_then(|metadata| metadata.pointer("/kernelspec/display_name")).and_then(Value::as_str);
+
+        let mut converted = match kernel {
+            Some(kernel) => format!("Jupyter notebook, kernel: {} ({})\n", kernel, language),
+            None => format!("Jupyter notebook ({})\n", language),
+        };
+        for cell in cells {
+            let source = cell_source(cell.get("source")).ok_or("a cell without source")?;
+            let source = source.trim_end();
+            match cell.get("cell_type").and_then(Value::as_str) {
+                Some("markdown") => converted.push_str(&format!("\n{}\n", source)),
+                Some("code") => {
+                    let fence = fence(source);
+                    converted.push_str(&format!("\n{}{}\n{}\n{}\n", fence, language, source, fence));
+                    if let Some(summary) = output_summary(cell) {
+                        converted.push_str(&format!("\n[output omitted: {}]\n", summary));
+                    }
+                }
+                Some("raw") => {
+                    let fence = fence(source);
+                    converted.push_str(&format!("\n{}\n{}\n{}\n", fence, source, fence));
+                }
+                Some(other) => return Err(format!("unknown cell type '{}'", other)),
+                None => return Err("a cell without type".to_string()),
+            }
+        }
+        Ok(Conversion { text: converted, language: "markdown" })
+    }
+}
+
+/// Cell source, stored as one string or as a list of lines
+fn cell_source(source: Option<&Value>) -> Option<String> {
+    match source? {
+        Value::String(source) => Some(source.clone()),
+        Value::Array(lines) => lines.iter().map(Value::as_str).collect(),
+        _ => None,
+    }
+}
+
+/// Describes the outputs of a code cell, e.g. `2 outputs, 1 image` (None without outputs)
+fn output_summary(cell: &Value) -> Option<String> {
+    let outputs = cell.get("outputs").and_then(Value::as_array).filter(|outputs| !outputs.is_empty())?;
+    let images = outputs
+        .iter()
+        .filter(|output| {
+            output
+                .get("data")
+                .and_then(Value::as_object)
+                .is_some_and(|data| data.keys().any(|mime_type| mime_type.starts_with("image/")))
+        })
+        .count();
+    let mut summary = format!("{} {}", outputs.len(), if outputs.len() == 1 { "output" } else { "outputs" });
+    if images > 0 {
+        summary.push_str(&format!(", {} {}", images, if images == 1 { "image" } else { "images" }));
+    }
+    Some(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/notebook.ipynb");
+
+    #[test]
+    fn converters_are_picked_by_extension() {
+        assert!(for_file("analysis.ipynb").is_some());
+        assert!(for_file("Analysis.IPYNB").is_some());
+        assert!(for_file("analysis.py").is_none());
+        assert!(for_file("ipynb").is_none());
+    }
+
+    #[test]
+    fn notebook_cells_become_markdown_and_fenced_code() {
+        let conversion = Notebook.convert(FIXTURE).unwrap();
+        assert_eq!(conversion.language, "markdown");
+        assert_eq!(
+            conversion.text,
+            "Jupyter notebook, kernel: Python 3 (ipykernel) (python)\n\
+             \n# Sales analysis\n\nLoads the data and plots revenue per month.\n\
+             \n```python\nimport pandas as pd\ndf = pd.read_csv(\"sales.csv\")\n```\n\
+             \n```python\nprint(len(df), \"rows\")\ndf.groupby(\"month\").revenue.sum().plot()\n```\n\
+             \n[output omitted: 2 outputs, 1 image]\n\
+             \n```\nExported on 2026-01-31\n```\n"
+        );
+    }
+
+    #[test]
+    fn notebook_outputs_and_images_are_left_out() {
+        let text = Notebook.convert(FIXTURE).unwrap().text;
+        assert!(!text.contains("iVBORw0KGgo"));
+        assert!(!text.contains("1200 rows"));
+        assert_eq!(text.matches("[output omitted").count(), 1);
+    }
+
+    #[test]
+    fn notebook_language_falls_back_to_kernelspec_then_python() {
+        let kernelspec = r#"{"cells": [], "metadata": {"kernelspec": {"display_name": "R", "language": "R"}}}"#;
+        assert_eq!(Notebook.convert(kernelspec).unwrap().text, "Jupyter notebook, kernel: R (R)\n");
+        let bare = r#"{"cells": [{"cell_type": "code", "source": "x = 1", "outputs": []}]}"#;
+        assert_eq!(Notebook.convert(bare).unwrap().text, "Jupyter notebook (python)\n\n```python\nx = 1\n```\n");
+    }
+
+    #[test]
+    fn malformed_notebooks_are_errors() {
+        assert!(Notebook.convert(r#"{"cells": ["#).is_err());
+        assert!(Notebook.convert(r#"{"metadata": {}}"#).is_err());
+        assert!(Notebook.convert(r#"{"cells": [{"cell_type": "code"}]}"#).is_err());
+        assert!(Notebook.convert(r#"{"cells": [{"cell_type": "widget", "source": ""}]}"#).is_err());
+    }
+
+    #[test]
+    fn fences_are_longer_than_any_backtick_run() {
+        assert_eq!(fence("plain"), "```");
+        assert_eq!(fence("inline `code`"), "```");
+        assert_eq!(fence("```python\nx\n```"), "````");
+        assert_eq!(fence("`````"), "``````");
+    }
+}
@@ -0,0 +1,236 @@
+//! Fetching GitHub repositories through the REST API instead of cloning (--via-api).
+//! The whole tree comes from one tarball download (`/tarball/<ref>`), so a run costs a single API request
+//! however many files the repository has. The archive is streamed through `curl` and only the selected
+//! paths are extracted, into a directory that is then processed like a checkout. Neither git nor a full
+//! clone is needed, but `curl` is; without it the caller clones instead.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use flate2::read::GzDecoder;
+use log::{debug, info};
+use tar::EntryType;
+
+/// A repository on github.com
+#[derive(Debug, PartialEq, Eq)]
+pub struct Repo {
+    owner: String,
+    name: String,
+}
+
+impl Repo {
+    /// Recognizes github.com URLs: https://github.com/owner/repo(.git), git@github.com:owner/repo.git
+    /// and ssh://git@github.com/owner/repo
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = ["https://github.com/", "http://github.com/", "git@github.com:", "ssh://git@github.com/"]
+            .iter()
+            .find_map(|prefix| url.trim().strip_prefix(prefix))?;
+        let rest = rest.trim_end_matches('/');
+        let rest = rest.strip_suffix(".git").unwrap_or(rest);
+        match rest.split('/').collect::<Vec<_>>()[..] {
+            [owner, name] if !owner.is_empty() && !name.is_empty() => {
+                Some(Self { owner: owner.to_string(), name: name.to_string() })
+            }
+            _ => None,
+        }
+    }
+
+    /// Writes the files of `branch` (default: the default branch) to `dest`, limited to `paths` if not empty.
+    /// Errors (e.g. rate limits, or curl missing) are returned so the caller can clone instead.
+    pub fn fetch(&self, branch: Option<&str>, paths: &[String], token: Option<&str>, dest: &Path) -> Result<(), String> {
+        let mut url = format!("https://api.github.com/repos/{}/{}/tarball", self.owner, self.name);
+        if let Some(branch) = branch {
+            url = format!("{}/{}", url, branch);
+        }
+        info!("Downloading {}/{} through the GitHub API...", self.owner, self.name);
+
+        let mut child = curl(&url, token)?;
+        let archive = child.stdout.take().ok_or("curl has no output")?;
+        // Dropping the archive on errors stops curl, so waiting for it can't block
+        let extracted = extract(archive, paths, dest);
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        let files = extracted?;
+        info!("Extracted {} files of {}/{}", files, self.owner, self.name);
+        Ok(())
+    }
+}
+
+/// Whether a path of the repository is selected by `paths` (empty = everything). Parent directories of
+/// selected paths are selected as well.
+fn selected(paths: &[String], path: &str) -> bool {
+    paths.is_empty()
+        || paths.iter().any(|filter| {
+            let filter = filter.trim_end_matches('/');
+            path == filter || path.starts_with(&format!("{}/", filter)) || filter.starts_with(&format!("{}/", path))
+        })
+}
+
+/// Extracts a gzip-compressed GitHub tarball to `dest`, limited to `paths` if not empty, and returns the number
+/// of files written. Entries sit below one top-level directory (`owner-repo-sha/`), which is dropped; symlinks
+/// are skipped and entries that would land outside `dest` are refused.
+fn extract(archive: impl Read, paths: &[String], dest: &Path) -> Result<usize, String> {
+    let mut archive = tar::Archive::new(GzDecoder::new(archive));
+    let mut files = 0;
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path().map_err(|e| e.to_string())?.into_owned();
+        let relative: PathBuf = path.components().skip(1).collect();
+        if relative.components().any(|component| !matches!(component, Component::Normal(_))) {
+            return Err(format!("the archive contains an unsafe path '{}'", path.display()));
+        }
+        let Some(relative_path) = relative.to_str().filter(|path| !path.is_empty()) else {
+            continue;
+        };
+        if !selected(paths, relative_path) {
+            continue;
+        }
+
+        let target = dest.join(&relative);
+        match entry.header().entry_type() {
+            EntryType::Directory => fs::create_dir_all(&target).map_err(|e| e.to_string())?,
+            EntryType::Regular | EntryType::Continuous => {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                let mut file = fs::File::create(&target).map_err(|e| e.to_string())?;
+                io::copy(&mut entry, &mut file).map_err(|e| e.to_string())?;
+                #[cfg(unix)]
+                if entry.header().mode().is_ok_and(|mode| mode & 0o111 != 0) {
+                    use std::os::unix::fs::PermissionsExt;
+                    fs::set_permissions(&target, fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+                }
+                files += 1;
+            }
+            EntryType::Symlink | EntryType::Link => debug!("Skipping link {}", relative_path),
+            _ => {}
+        }
+    }
+    Ok(files)
+}
+
+/// Starts a GET request with curl, with the response on its stdout.
+/// The token is passed as a header on stdin, so it never appears on a command line.
+fn curl(url: &str, token: Option<&str>) -> Result<Child, String> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location", "--header", "@-"])
+        .args(["--header", "Accept: application/vnd.github+json"])
+        .args(["--header", concat!("User-Agent: ", env!("CARGO_PKG_NAME"))])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => "curl is not installed".to_string(),
+            _ => format!("unable to run curl: {}", e),
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(token) = token {
+            writeln!(stdin, "Authorization: Bearer {}", token).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    /// Adds an entry to a tarball under a raw name (the tar crate refuses to set unsafe paths)
+    fn append(builder: &mut tar::Builder<GzEncoder<Vec<u8>>>, name: &str, entry_type: EntryType, mode: u32, data: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_mode(mode);
+        header.set_size(data.len() as u64);
+        if entry_type == EntryType::Symlink {
+            header.set_link_name("README.md").unwrap();
+        }
+        header.set_cksum();
+        builder.append(&header, data).unwrap();
+    }
+
+    /// A tarball shaped like GitHub's, with a pax global header and a top-level directory
+    fn tarball(extra: &[(&str, EntryType, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        append(&mut builder, "pax_global_header", EntryType::XGlobalHeader, 0o666, b"52 comment=0123456789abcdef\n");
+        append(&mut builder, "octo-hello-0123456/", EntryType::Directory, 0o775, b"");
+        append(&mut builder, "octo-hello-0123456/README.md", EntryType::Regular, 0o664, b"# Hello\n");
+        append(&mut builder, "octo-hello-0123456/src/", EntryType::Directory, 0o775, b"");
+        append(&mut builder, "octo-hello-0123456/src/main.rs", EntryType::Regular, 0o664, b"fn main() {}\n");
+        append(&mut builder, "octo-hello-0123456/run.sh", EntryType::Regular, 0o775, b"#!/bin/sh\n");
+        append(&mut builder, "octo-hello-0123456/docs/", EntryType::Directory, 0o775, b"");
+        append(&mut builder, "octo-hello-0123456/docs/guide.md", EntryType::Regular, 0o664, b"Guide\n");
+        append(&mut builder, "octo-hello-0123456/link.md", EntryType::Symlink, 0o777, b"");
+        for (name, entry_type, data) in extra {
+            append(&mut builder, name, *entry_type, 0o664, data);
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn github_urls_are_recognized() {
+        let repo = Repo { owner: "octo".to_string(), name: "hello".to_string() };
+        for url in [
+            "https://github.com/octo/hello",
+            "https://github.com/octo/hello.git",
+            "https://github.com/octo/hello/",
+            "git@github.com:octo/hello.git",
+            "ssh://git@github.com/octo/hello",
+        ] {
+            assert_eq!(Repo::parse(url).as_ref(), Some(&repo), "{}", url);
+        }
+        for url in ["https://gitlab.com/octo/hello", "https://github.com/octo", "https://github.com/octo/hello/tree/main"] {
+            assert_eq!(Repo::parse(url), None, "{}", url);
+        }
+    }
+
+    #[test]
+    fn tarballs_are_extracted_without_the_top_level_directory() {
+        let dest = tempfile::tempdir().unwrap();
+        assert_eq!(extract(&tarball(&[])[..], &[], dest.path()), Ok(4));
+        assert_eq!(fs::read_to_string(dest.path().join("README.md")).unwrap(), "# Hello\n");
+        assert_eq!(fs::read_to_string(dest.path().join("src/main.rs")).unwrap(), "fn main() {}\n");
+        assert!(dest.path().join("docs/guide.md").is_file());
+        assert!(!dest.path().join("link.md").exists());
+        assert!(!dest.path().join("pax_global_header").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &str| fs::metadata(dest.path().join(path)).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode("run.sh"), 0o755);
+            assert_eq!(mode("README.md") & 0o111, 0);
+        }
+    }
+
+    #[test]
+    fn only_selected_paths_are_extracted() {
+        let dest = tempfile::tempdir().unwrap();
+        let paths = ["src/".to_string(), "README.md".to_string()];
+        assert_eq!(extract(&tarball(&[])[..], &paths, dest.path()), Ok(2));
+        assert!(dest.path().join("src/main.rs").is_file());
+        assert!(dest.path().join("README.md").is_file());
+        assert!(!dest.path().join("docs").exists());
+        assert!(!dest.path().join("run.sh").exists());
+    }
+
+    #[test]
+    fn unsafe_paths_are_refused() {
+        let dest = tempfile::tempdir().unwrap();
+        let archive = tarball(&[("octo-hello-0123456/../../escaped.txt", EntryType::Regular, b"x")]);
+        assert!(extract(&archive[..], &[], &dest.path().join("repo")).unwrap_err().contains("unsafe path"));
+        assert!(!dest.path().join("escaped.txt").exists());
+    }
+
+    #[test]
+    fn broken_archives_are_errors() {
+        let dest = tempfile::tempdir().unwrap();
+        assert!(extract(&b"{\"message\": \"Not Found\"}"[..], &[], dest.path()).is_err());
+    }
+}
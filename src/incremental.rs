@@ -0,0 +1,209 @@
+//! Incremental regeneration (--incremental).
+//! A manifest next to the output records every file's size, mtime and hash together with the byte range
+//! of its section, so the next run only re-reads files that changed and copies the other sections over.
+//! The output itself is checked the same way as the sources, by size and mtime: hashing a document of
+//! many megabytes on every run would cost more than the sections it saves.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use serde::{Deserialize, Serialize};
+
+use super::{sha256_hex, ConcatError};
+use super::report::Entry;
+
+/// Manifest format version; manifests of other versions are ignored
+const VERSION: u32 = 1;
+
+/// Flags that don't change the generated document and may differ between runs
+const NEUTRAL_FLAGS: [&str; 8] = ["-q", "--quiet", "-v", "-vv", "--verbose", "--incremental", "--clipboard", "--strict"];
+
+/// Options with a value that don't change the generated document
+const NEUTRAL_OPTIONS: [&str; 3] = ["--report", "--manifest", "--strict-on"];
+
+/// What the previous run emitted for one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileState {
+    pub size: u64,                  // File size in bytes
+    pub mtime: u64,                 // Modification time in nanoseconds since the Unix epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,     // Hash of the file as stored on disk (None if unreadable)
+    pub heading: String,            // Section heading; a different heading means the section must be rendered again
+    pub offset: usize,              // Byte offset of the section in the output
+    pub length: usize,              // Section length in bytes
+    pub lines: usize,               // Lines of text, for --stats
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,   // Source encoding for transcoded files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub classification: Option<String>, // "generated" or "minified"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<Entry>,         // Report entries recorded while processing the file
+}
+
+/// Sidecar file describing a generated document
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,                       // Manifest format version
+    pub options: String,                    // Fingerprint of the options the document was generated with
+    pub document_size: u64,                 // Size of the document the offsets refer to
+    pub document_mtime: u64,                // Its modification time in nanoseconds since the Unix epoch
+    pub files: BTreeMap<String, FileState>, // Relative path -> state of the file
+}
+
+impl Manifest {
+    /// Describes the document written to `output`, generated with the options fingerprinted as `options`
+    pub fn new(options: &str, output: &Path, files: BTreeMap<String, FileState>) -> Self {
+        Self {
+            version: VERSION,
+            options: options.to_string(),
+            document_size: fs::metadata(output).map(|metadata| metadata.len()).unwrap_or(0),
+            document_mtime: modified(output).unwrap_or(0),
+            files,
+        }
+    }
+
+    /// Writes the manifest as JSON
+    pub fn save(&self, path: &Path) -> Result<(), ConcatError> {
+        let json = serde_json::to_string(self)?;
+        fs::write(path, json + "\n").map_err(|source| ConcatError::OutputWrite { path: path.to_path_buf(), source })
+    }
+}
+
+/// Output of the previous run, whose unchanged sections can be reused
+#[derive(Debug)]
+pub struct Previous {
+    files: BTreeMap<String, FileState>,
+    text: String,
+}
+
+impl Previous {
+    /// Loads the previous output and its manifest. Errors describe why a full rebuild is needed.
+    pub fn load(output: &Path, options: &str) -> Result<Self, String> {
+        let json = fs::read_to_string(manifest_path(output)).map_err(|_| "no manifest from a previous run".to_string())?;
+        let manifest: Manifest = serde_json::from_str(&json).map_err(|_| "the manifest is unreadable".to_string())?;
+        if manifest.version != VERSION {
+            return Err(format!("the manifest has version {} (expected {})", manifest.version, VERSION));
+        }
+        if manifest.options != options {
+            return Err("the options differ from the previous run".to_string());
+        }
+        let size = fs::metadata(output).map(|metadata| metadata.len()).ok();
+        if size != Some(manifest.document_size) || modified(output) != Some(manifest.document_mtime) {
+            return Err("the previous output was modified".to_string());
+        }
+        let text = fs::read_to_string(output).map_err(|_| "the previous output is unreadable".to_string())?;
+        Ok(Self { files: manifest.files, text })
+    }
+
+    /// The previous state and section of a file, if its size, mtime and heading are unchanged
+    pub fn section(&self, path: &str, size: Option<u64>, mtime: Option<u64>, heading: &str) -> Option<(&FileState, &str)> {
+        let file = self.files.get(path)?;
+        if Some(file.size) != size || Some(file.mtime) != mtime || file.heading != heading {
+            return None;
+        }
+        let section = self.text.get(file.offset..file.offset + file.length)?;
+        Some((file, section))
+    }
+}
+
+/// Manifest path for an output file: `<output>.manifest.json`
+pub fn manifest_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_owned();
+    name.push(".manifest.json");
+    PathBuf::from(name)
+}
+
+/// Fingerprint of the tool version and command line, ignoring options that don't affect the document
+pub fn fingerprint(args: &[String]) -> String {
+    let mut key = format!("{}\0", env!("CARGO_PKG_VERSION"));
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if NEUTRAL_OPTIONS.contains(&arg.as_str()) {
+            args.next();
+            continue;
+        }
+        if NEUTRAL_FLAGS.contains(&arg.as_str()) {
+            continue;
+        }
+        key.push_str(arg);
+        key.push('\0');
+    }
+    sha256_hex(key.as_bytes())
+}
+
+/// Modification time of a file in nanoseconds since the Unix epoch
+pub fn modified(path: &Path) -> Option<u64> {
+    let mtime = fs::metadata(path).ok()?.modified().ok()?;
+    Some(mtime.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn fingerprints_ignore_neutral_options() {
+        let base = fingerprint(&args(&["repo", "--stats"]));
+        assert_eq!(base, fingerprint(&args(&["repo", "-v", "--stats", "--incremental", "--report", "r.json"])));
+        assert_eq!(base, fingerprint(&args(&["repo", "--stats", "--manifest", "m.json", "--strict-on", "binary", "-q"])));
+        assert_ne!(base, fingerprint(&args(&["repo"])));
+        assert_ne!(base, fingerprint(&args(&["repo", "--stats", "--toc"])));
+        // Values of other options count, and arguments can't run together
+        assert_ne!(fingerprint(&args(&["repo", "--max-lines", "10"])), fingerprint(&args(&["repo", "--max-lines", "20"])));
+        assert_ne!(fingerprint(&args(&["ab", "c"])), fingerprint(&args(&["a", "bc"])));
+    }
+
+    #[test]
+    fn sections_are_only_reused_for_unchanged_files() {
+        let text = "# Header\n\n## a.rs\n\nfn a() {}\n\n## b.rs\n\nfn b() {}\n\n".to_string();
+        let state = |offset: usize, length: usize| FileState {
+            size: 9,
+            mtime: 100,
+            sha256: None,
+            heading: "## a.rs".to_string(),
+            offset,
+            length,
+            lines: 1,
+            encoding: None,
+            classification: None,
+            events: Vec::new(),
+        };
+        let mut files = BTreeMap::new();
+        files.insert("a.rs".to_string(), state(10, 20));
+        files.insert("gone.rs".to_string(), state(1000, 18));
+        let previous = Previous { files, text };
+
+        let (_, section) = previous.section("a.rs", Some(9), Some(100), "## a.rs").unwrap();
+        assert_eq!(section, "## a.rs\n\nfn a() {}\n\n");
+        assert!(previous.section("a.rs", Some(10), Some(100), "## a.rs").is_none());
+        assert!(previous.section("a.rs", Some(9), Some(101), "## a.rs").is_none());
+        assert!(previous.section("a.rs", Some(9), Some(100), "## src/a.rs").is_none());
+        assert!(previous.section("new.rs", Some(9), Some(100), "## a.rs").is_none());
+        // Ranges outside the previous output are never trusted
+        assert!(previous.section("gone.rs", Some(9), Some(100), "## a.rs").is_none());
+    }
+
+    #[test]
+    fn missing_manifests_mean_a_full_rebuild() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("repo.md");
+        assert_eq!(Previous::load(&output, "options").unwrap_err(), "no manifest from a previous run");
+
+        fs::write(&output, "document").unwrap();
+        Manifest::new("options", &output, BTreeMap::new()).save(&manifest_path(&output)).unwrap();
+        assert!(Previous::load(&output, "options").is_ok());
+        assert_eq!(Previous::load(&output, "other").unwrap_err(), "the options differ from the previous run");
+
+        // Rewriting the output with contents of the same size still changes its mtime
+        let mtime = modified(&output).unwrap();
+        fs::write(&output, "DOCUMENT").unwrap();
+        let file = fs::File::options().write(true).open(&output).unwrap();
+        file.set_modified(UNIX_EPOCH + std::time::Duration::from_nanos(mtime + 1_000_000)).unwrap();
+        assert_eq!(Previous::load(&output, "options").unwrap_err(), "the previous output was modified");
+    }
+}
@@ -0,0 +1,40 @@
+//! Diagnostics setup on top of the `log` crate.
+//! Diagnostics go to stderr at the level chosen with -q/-v/-vv (or RUST_LOG), keeping stdout for results.
+
+use std::io::Write;
+use log::{Level, LevelFilter};
+
+/// Maps the -q/-v flags to a log level: -q = errors only, default = warnings, -v = info, -vv = debug
+pub fn level_from_flags(quiet: bool, verbose: u8) -> LevelFilter {
+    match (quiet, verbose) {
+        (true, _) => LevelFilter::Error,
+        (false, 0) => LevelFilter::Warn,
+        (false, 1) => LevelFilter::Info,
+        (false, 2) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    }
+}
+
+/// Installs the logger; RUST_LOG, when set, takes precedence over the flags
+pub fn init(level: LevelFilter) {
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(level);
+    if let Ok(filters) = std::env::var("RUST_LOG") {
+        builder.parse_filters(&filters);
+    }
+    builder.format(|buf, record| match record.level() {
+        Level::Error => writeln!(buf, "Error: {}", record.args()),
+        Level::Warn => writeln!(buf, "Warning: {}", record.args()),
+        _ => writeln!(buf, "{}", record.args()),
+    });
+    builder.init();
+}
+
+/// Prints a result line (e.g. the generated file) to stdout unless --quiet
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if log::max_level() >= log::LevelFilter::Warn {
+            println!($($arg)*);
+        }
+    };
+}
@@ -2,308 +2,4809 @@
 //! This tool takes any Git repository (local path or remote URL) and creates a single Markdown file
 //! containing the repository structure and all file contents.
 
-use std::collections::HashSet;
+#[macro_use]
+mod logging;
+mod audit;
+mod config_file;
+mod convert;
+mod github;
+mod incremental;
+mod location;
+mod preset;
+mod report;
+mod selection;
+mod sidecar;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::io;
 use serde::{Serialize, Deserialize};
 use tempfile::TempDir;
+use base64::Engine;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use sha2::{Digest, Sha256};
+use log::{debug, info, warn};
+use audit::{Auditor, Finding};
+use config_file::ConfigFile;
+use incremental::{FileState, Manifest, Previous};
+use preset::Preset;
+use report::{Reason, Report};
+use sidecar::{Sidecar, SidecarFile, Skipped};
+use selection::Selection;
+
+/// Errors that can abort a run, each mapped to its own exit code
+#[derive(Debug, thiserror::Error)]
+enum ConcatError {
+    #[error("{0}")]
+    InvalidArguments(String),
+    #[error("Repository path '{0}' does not exist or is not a directory")]
+    RepoNotFound(String),
+    #[error("Failed to clone '{url}': {stderr}")]
+    CloneFailed { url: String, stderr: String },
+    #[error("Failed to check out '{git_ref}': {stderr}")]
+    CheckoutFailed { git_ref: String, stderr: String },
+    #[error("I/O error on '{}': {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Failed to write output '{}': {source}", path.display())]
+    OutputWrite { path: PathBuf, source: io::Error },
+    #[error("Failed to serialize output: {0}")]
+    Serialize(String),
+    #[error("Clipboard unavailable: {0}")]
+    Clipboard(String),
+    #[error("Output would exceed the size limit of {limit} bytes (raise it with --max-output-size or use --on-limit truncate)")]
+    OutputTooLarge { limit: u64 },
+    #[error("Problems found with --strict: {0}")]
+    Strict(String),
+    #[error("The audit found {0} risky files or matches (--fail-on-audit), no output written")]
+    AuditFailed(usize),
+}
+
+impl ConcatError {
+    /// Wraps an I/O error together with the path it occurred on
+    fn io(path: &Path, source: io::Error) -> Self {
+        Self::Io { path: path.to_path_buf(), source }
+    }
+
+    /// Process exit code for this error
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::InvalidArguments(_) => 2,
+            Self::RepoNotFound(_) => 3,
+            Self::CloneFailed { .. } | Self::CheckoutFailed { .. } => 4,
+            Self::OutputWrite { .. } => 5,
+            Self::Clipboard(_) => 6,
+            Self::OutputTooLarge { .. } => 7,
+            Self::Strict(_) => 8,
+            Self::AuditFailed(_) => 9,
+            Self::Io { .. } | Self::Serialize(_) => 1,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ConcatError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Serialize(e.to_string())
+    }
+}
+
+impl From<serde_yaml::Error> for ConcatError {
+    fn from(e: serde_yaml::Error) -> Self {
+        Self::Serialize(e.to_string())
+    }
+}
 
 /// Represents a file or directory in the repository structure
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 struct FileEntry {
     #[serde(rename = "type")]
-    entry_type: String,      // "file" or "directory"
+    entry_type: String,      // "file", "directory" or "submodule" (placeholder for submodule contents not included)
     name: String,            // Name of the file or directory
     path: String,            // Relative path from repository root
     #[serde(skip_serializing_if = "Option::is_none")]
-    size: Option<u64>,       // File size in bytes (None for directories)
+    size: Option<u64>,       // File size in bytes (total of the included files for directories)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_count: Option<usize>, // Number of included files below a directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lines: Option<usize>,    // Lines of text (total for directories; None when contents are not read)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated: Option<bool>,  // Set when --max-depth stopped the walk at this directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mode: Option<String>,     // Unix permission bits in octal, e.g. "0755" (only with --show-mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<String>, // Source encoding for files transcoded to UTF-8
+    #[serde(skip_serializing_if = "Option::is_none")]
+    classification: Option<String>, // "generated" or "minified" when detected as such
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,     // SHA-256 of the file as stored on disk (only with --hash)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,     // Configured URL of a submodule placeholder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,   // Why the entry could not be read (e.g. permission denied); its contents are left out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<String>, // Date of the last commit touching the file, or of its mtime (only with --modified-since)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stale: Option<bool>,     // Set when the file was last modified before --modified-since; its contents are left out
     #[serde(skip_serializing_if = "Option::is_none")]
     children: Option<Vec<FileEntry>>,  // Subdirectories and files (None for files)
 }
 
-/// Main processor struct that handles all file operations
-struct FileProcessor {
-    ignore_dirs: HashSet<String>,       // Directories to ignore (e.g., .git, node_modules)
-    ignore_files: HashSet<String>,      // Files to ignore (e.g., .DS_Store)
-    ignore_extensions: HashSet<String>, // File extensions to ignore (e.g., .exe, .dll)
+/// A single file serialized as one line of JSON Lines output
+#[derive(Debug, Serialize)]
+struct FileRecord<'a> {
+    path: &'a str,             // Relative path from repository root
+    language: &'a str,         // Detected language (empty if unknown)
+    size: Option<u64>,         // File size in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,    // Hash of the emitted text, or of the original bytes for embedded binary files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,   // File contents (None if skipped)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<&'static str>, // "base64" for embedded binary files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped_reason: Option<String>, // Why the content was skipped
 }
 
-impl FileProcessor {
-    /// Creates a new FileProcessor with default ignore lists
-    fn new() -> Self {
-        // Initialize directories to ignore
-        let mut ignore_dirs = HashSet::new();
-        ignore_dirs.insert(".git".to_string());
-        ignore_dirs.insert("node_modules".to_string());
-        ignore_dirs.insert("target".to_string());
-        ignore_dirs.insert("dist".to_string());
-        ignore_dirs.insert("build".to_string());
+/// Final line of JSON Lines output summarizing the run
+#[derive(Debug, Serialize)]
+struct SummaryRecord {
+    #[serde(rename = "type")]
+    record_type: &'static str, // Always "summary"
+    files: usize,              // Files processed
+    bytes: u64,                // Total size of the processed files
+    skipped: usize,            // Files whose content was skipped as binary
+    generated: usize,          // Files classified as generated or minified
+    duplicates: usize,         // Files collapsed as duplicates
+    normalized: usize,         // Files whose line endings were converted
+    deleted: usize,            // Deleted files listed with --since
+    omitted: usize,            // Files left out after reaching the output size limit
+}
 
-        // Initialize files to ignore
-        let mut ignore_files = HashSet::new();
-        ignore_files.insert(".DS_Store".to_string());
-        ignore_files.insert("yarn.lock".to_string());
+/// The generated output, with the offsets where file sections start (candidate `--split` points)
+#[derive(Default)]
+struct Document {
+    text: String,       // Complete document (only the part before the file sections when they were streamed)
+    breaks: Vec<usize>, // Byte offsets of file section starts, ascending
+    report: Report,     // Files that were skipped, shortened or unreadable
+    files: BTreeMap<String, FileState>, // Emitted files, with section offsets in the document (--incremental)
+    sidecar: Option<Sidecar>, // Description of the document (--manifest)
+}
 
-        // Initialize file extensions to ignore
-        let mut ignore_extensions = HashSet::new();
-        for ext in [
-            // Binaries
-            "exe", "dll", "so", "dylib",
-            // Images
-            "png", "jpg", "jpeg", "gif", "ico", "bmp", "tiff", "webp",
-            // Archives
-            "zip", "rar", "7z", "tar", "gz", "bz2",
-            // Other binary formats
-            "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx",
-            "class", "pyc", "pyo", "pyd",
-            // Audio/Video
-            "mp3", "mp4", "wav", "avi", "mov", "flv", "mkv",
-            // Database
-            "db", "sqlite", "sqlite3",
-        ] {
-            ignore_extensions.insert(ext.to_string());
-        }
+impl Document {
+    /// Splits the document at file boundaries into parts of at most `limit` bytes, each after the first
+    /// starting with a continuation header. A single file larger than the limit gets a part of its own.
+    fn split(&self, limit: u64, name: &str) -> Vec<String> {
+        // Leave room for the continuation header
+        let budget = limit.saturating_sub(SPLIT_HEADER_RESERVE) as usize;
 
-        Self {
-            ignore_dirs,
-            ignore_files,
-            ignore_extensions,
+        let mut ranges = Vec::new();
+        let (mut start, mut end) = (0, 0);
+        for &boundary in self.breaks.iter().chain(std::iter::once(&self.text.len())) {
+            if boundary <= end {
+                continue;
+            }
+            if boundary - start > budget && end > start {
+                ranges.push(start..end);
+                start = end;
+            }
+            end = boundary;
         }
+        ranges.push(start..end);
+
+        let count = ranges.len();
+        ranges
+            .into_iter()
+            .enumerate()
+            .map(|(index, range)| match index {
+                0 => self.text[range].to_string(),
+                _ => format!(
+                    "<!-- {} part {} of {} -->\n\n# File Contents (continued)\n\n{}",
+                    name,
+                    index + 1,
+                    count,
+                    &self.text[range]
+                ),
+            })
+            .collect()
     }
+}
 
-    /// Recursively builds the file structure starting from the given directory
-    fn get_file_structure(&self, dir: &Path, base_path: &Path) -> Result<Vec<FileEntry>, Box<dyn Error>> {
-        let mut structure = Vec::new();
-        let entries = fs::read_dir(dir)?;
+/// Bytes kept free in every `--split` part for the continuation header
+const SPLIT_HEADER_RESERVE: u64 = 256;
 
-        // Process each entry in the directory
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-            let name = entry.file_name().to_string_lossy().into_owned();
-            let relative_path = base_path.join(&name);
-
-            if path.is_dir() {
-                // Process directory if it's not in ignore list
-                if !self.ignore_dirs.contains(&name) {
-                    let children = self.get_file_structure(&path, &relative_path)?;
-                    if !children.is_empty() {
-                        structure.push(FileEntry {
-                            entry_type: "directory".to_string(),
-                            name,
-                            path: relative_path.to_string_lossy().into_owned(),
-                            size: None,
-                            children: Some(children),
-                        });
-                    }
-                }
-            } else {
-                // Process file if it's not in ignore list
-                if !self.should_ignore_file(&name) {
-                    structure.push(FileEntry {
-                        entry_type: "file".to_string(),
-                        name,
-                        path: relative_path.to_string_lossy().into_owned(),
-                        size: Some(entry.metadata()?.len()),
-                        children: None,
-                    });
-                }
+/// Document metadata emitted as YAML front matter
+#[derive(Debug, Serialize)]
+struct FrontMatter<'a> {
+    repository: &'a str,    // Repository name (same as the output file stem)
+    source: &'a str,        // Local path or remote URL as given on the command line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit: Option<String>, // HEAD commit SHA, if the source is a git repository
+    #[serde(skip_serializing_if = "Option::is_none")]
+    branch: Option<String>, // Checked out branch, if any
+    generated_at: String,   // RFC 3339 generation timestamp (UTC)
+    generator: String,      // Tool name and version
+    file_count: usize,      // Number of files in the structure
+    total_size: u64,        // Sum of file sizes in bytes
+}
+
+/// Content of a single file as it will be emitted
+enum FileContent {
+    Text(String),     // UTF-8 text content
+    Binary(Vec<u8>),  // Binary content to be embedded as base64
+    Image(&'static str, Vec<u8>), // Image to be shown inline as a data URI, with its MIME type
+    Skipped(String),  // Content omitted, with the reason why
+    Duplicate(String), // Identical to an earlier file at the given path
+    Generated(usize), // Generated or minified content left out, with its size in bytes
+    TooLong(usize),   // Content left out for exceeding --max-lines, with its line count
+    OverLimit(String), // Content left out for exceeding a --limit rule, with the size and the rule
+    Unreadable(String), // Reading failed, with the error message
+}
+
+/// Mutable state accumulated while processing files
+#[derive(Debug, Default)]
+struct ProcessState<'a> {
+    seen_hashes: HashMap<String, String>, // Content hash -> first path with that content
+    duplicates: usize,                    // Number of duplicate files collapsed
+    bytes_saved: u64,                     // Bytes not emitted thanks to deduplication
+    languages: BTreeMap<String, LanguageStats>, // Per-language totals for the stats table
+    generated: usize,                     // Files classified as generated or minified
+    report: Report,                       // Skipped, shortened and unreadable files
+    omitted: Vec<String>,                 // Files left out after reaching the output size limit
+    limit_reached: bool,                  // Output size limit reached (--on-limit truncate); later files are omitted
+    stream: Option<&'a mut OutputWriter>, // Output that records or sections are streamed to (JSON Lines, --compress)
+    breaks: Vec<usize>,                   // Offsets in the contents where file sections start
+    files: BTreeMap<String, FileState>,   // Emitted files with their sections (--incremental, --manifest)
+    reused: usize,                        // Sections copied over from the previous output
+    normalized: usize,                    // Files whose line endings were converted
+    sections: Vec<String>,                // Paths of the emitted file sections, in document order
+    findings: Vec<Finding>,               // Risky files and matches (--audit)
+}
+
+impl ProcessState<'_> {
+    /// Appends a file's output to the document, or writes it straight to the output file when streaming
+    fn emit(&mut self, document: &mut String, text: &str) -> Result<(), ConcatError> {
+        match &mut self.stream {
+            Some(stream) => stream.write(text),
+            None => {
+                self.breaks.push(document.len());
+                document.push_str(text);
+                Ok(())
             }
         }
+    }
 
-        Ok(structure)
+    /// Bytes already written to the output file while streaming
+    fn streamed(&self) -> u64 {
+        self.stream.as_ref().map_or(0, |stream| stream.len)
     }
+}
 
-    /// Checks if a file should be ignored based on its name or extension
-    fn should_ignore_file(&self, filename: &str) -> bool {
-        // Check if the file is in the ignore list
-        if self.ignore_files.contains(filename) {
-            return true;
+/// Aggregated statistics for one detected language
+#[derive(Debug, Default)]
+struct LanguageStats {
+    files: usize, // Number of files
+    bytes: u64,   // Total size in bytes
+    lines: usize, // Total lines of text content
+}
+
+/// A field of `FileEntry` that can be selected for the structure section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StructureField {
+    Type,
+    Name,
+    Path,
+    Size,
+    Mode,
+    Language,
+    Encoding,
+    Hash,
+}
+
+impl StructureField {
+    /// Parses a comma-separated field list such as `path,size,language`
+    fn parse_list(list: &str) -> Result<Vec<Self>, String> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| match name {
+                "type" => Ok(Self::Type),
+                "name" => Ok(Self::Name),
+                "path" => Ok(Self::Path),
+                "size" => Ok(Self::Size),
+                "mode" => Ok(Self::Mode),
+                "language" => Ok(Self::Language),
+                "encoding" => Ok(Self::Encoding),
+                "hash" => Ok(Self::Hash),
+                _ => Err(format!(
+                    "Unknown structure field '{}' (expected type, name, path, size, mode, language, encoding or hash)",
+                    name
+                )),
+            })
+            .collect()
+    }
+
+    /// JSON key used for this field
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Type => "type",
+            Self::Name => "name",
+            Self::Path => "path",
+            Self::Size => "size",
+            Self::Mode => "mode",
+            Self::Language => "language",
+            Self::Encoding => "encoding",
+            Self::Hash => "hash",
         }
+    }
+}
 
-        // Check if the file extension is in the ignore list
-        if let Some(extension) = Path::new(filename).extension() {
-            self.ignore_extensions.contains(&extension.to_string_lossy().to_string())
-        } else {
-            false
+/// Supported output formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Markdown, // Structure plus fenced file contents (default)
+    Jsonl,    // One JSON object per file per line
+    Json,     // The structure tree as one JSON document (with --structure-only)
+    Text,     // Plain text: `===== path =====` separators and raw contents
+}
+
+impl OutputFormat {
+    /// Parses a format name given on the command line
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "jsonl" => Ok(Self::Jsonl),
+            "json" => Ok(Self::Json),
+            "text" | "txt" => Ok(Self::Text),
+            _ => Err(format!("Unknown format '{}' (expected markdown, text, jsonl or json)", name)),
         }
     }
 
-    /// Determines the programming language based on file extension
-    fn get_language_from_ext(&self, filepath: &Path) -> String {
-        let extension = filepath
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("")
-            .to_lowercase();
+    /// File extension used for the generated output
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Jsonl => "jsonl",
+            Self::Json => "json",
+            Self::Text => "txt",
+        }
+    }
+}
 
-        // Map file extensions to their corresponding language for syntax highlighting
-        match extension.as_str() {
-            "js" | "jsx" => "javascript",
-            "ts" | "tsx" => "typescript",
-            "py" => "python",
-            "rb" => "ruby",
-            "java" => "java",
-            "cs" => "csharp",
-            "cpp" | "hpp" => "cpp",
-            "c" | "h" => "c",
-            "rs" => "rust",
-            "go" => "go",
-            "php" => "php",
-            "html" => "html",
-            "css" => "css",
-            "scss" => "scss",
-            "md" => "markdown",
-            "json" => "json",
-            "xml" => "xml",
-            "yaml" | "yml" => "yaml",
-            "sh" | "bash" => "bash",
-            "sql" => "sql",
-            "kt" => "kotlin",
-            "swift" => "swift",
-            "r" => "r",
-            "lua" => "lua",
-            "pl" | "perl" => "perl",
-            "dart" => "dart",
-            "ex" | "exs" => "elixir",
-            "erl" => "erlang",
-            "fs" | "fsx" => "fsharp",
-            "hs" => "haskell",
-            "scala" => "scala",
-            "toml" => "toml",
-            _ => "",
-        }.to_string()
+/// Help text listing all command line options
+const OPTIONS_HELP: &str = "\
+  --format <format>           Output format: markdown (default), text, jsonl, or json (with --structure-only)
+  --embed-binary <max-size>   Embed binary files up to max-size as base64
+  --embed-images              Show images up to 100K inline as data URIs (markdown)
+  --dedup                     Collapse files with identical contents
+  --hash                      Add each file's SHA-256 to the structure
+  --keep-empty-dirs           Keep directories without included files
+  --front-matter              Prepend YAML front matter with document metadata
+  --stats                     Add a per-language statistics table after the structure
+  --toc                       Add a table of contents linking to every file section (markdown)
+  --structure-fields <list>   Comma-separated fields to include in the structure
+                              (type, name, path, size, mode, language, encoding, hash)
+  --structure-only            Only emit the structure, without reading file contents
+  --no-structure              Leave out the structure section
+  --lang <list>               Only include files of these languages, e.g. rust,toml
+                              ('other' covers files without a known language)
+  --exclude-lang <list>       Exclude files of these languages, e.g. json,yaml
+  --only-ext <ext>            Only include files with this extension (repeatable; '' = none)
+  --lang-map <ext=language>   Override the language for an extension or file name
+                              wildcard (repeatable), e.g. h=cpp or '*.tmpl=html'
+  --hidden, --include-hidden  Include all dotfiles and dot-directories (e.g. .idea)
+  --no-hidden                 Exclude all dotfiles and dot-directories
+  --include-env               Include .env* files (excluded by default)
+  --max-depth <n>             Stop descending below n directory levels
+  --max-lines <n>             Omit the contents of files with more than n lines
+  --limit <pattern=limit>     Omit the contents of matching files over a size or line limit, e.g.
+                              '**/*.json=10K' or '*.snap=50lines' (repeatable, first match wins
+                              over --max-lines)
+  --config <file>             Options file (TOML) with a [limits] table of pattern = limit rules
+  --preview-lines <n>         Only include the first and last n lines of longer files
+  --top <n>                   Print the n largest directories and files to stderr
+  --max-output-size <size>    Maximum size of the generated document (default: 500M,
+                              0 or 'none' disables the limit)
+  --on-limit <abort|truncate> Fail (default) or stop adding files at the size limit
+  --normalize-eol <style>     Line endings of text contents: lf (default), crlf or keep
+  --preserve-eol              Keep the original line endings (same as --normalize-eol keep)
+  --order <path|priority>     Order of file sections: structure order (default) or README,
+                              manifests, sources, tests, then everything else
+  --path-prefix <prefix>      Prepend prefix/ to every path in the output, e.g. the repo name
+  --flatten-paths             Show only file names in file headings (directory added on collisions)
+  --wrap <n>                  Hard-wrap content lines longer than n characters
+  --filter-cmd <cmd>          Pipe each text file through a shell command (path in $1 and CONCAT_FILE)
+  --split <size>              Write the document as numbered parts of at most size bytes
+  --show-mode                 Include Unix file permissions in the structure
+  --interactive               Choose the files to include from a checklist
+  --selection <file>          Include only the files listed in a saved selection
+  --save-selection <file>     Save the chosen files as a selection
+  --recurse-submodules        Include the contents of git submodules
+  --path <path>               Only include this file or directory (repeatable, same as
+                              positional paths); remote clones then fetch only these paths
+  --local                     Treat the argument as a local path, even if it looks like a URL
+  --remote                    Clone the argument, even if it is a local path
+  --no-cache                  Clone remotes into a temporary directory, bypassing the cache
+  --refresh-cache             Replace the cached clone of a remote with a fresh clone
+  --cache-dir <dir>           Clone cache location (default: user cache directory)
+  --clear-cache               Delete the clone cache (the repository argument is then optional)
+  --via-api                   Fetch github.com repositories through the GitHub API instead of cloning
+  --ref <ref>                 Read a local repository at this git ref, checked out in a temporary
+                              worktree (the working tree is left untouched)
+  --since <ref>               Only include files changed since the given git ref
+  --modified-since <when>     Only include the contents of files last modified after a date (2026-01-31)
+                              or duration ago (30d, 12h, 2w); older files stay in the structure
+  --concatignore <file>       Extra exclusion patterns (gitignore syntax)
+  --exclude <pattern>         Exclude paths matching a pattern (gitignore syntax, repeatable)
+  --include <pattern>         Re-include paths matching a pattern (repeatable)
+  --preset <name>             Built-in exclusion profile: code-only, docs-only or minimal
+  --show-ignores              Print the effective ignore rules and exit
+  --skip-generated            Skip files marked linguist-generated in .gitattributes
+  --include-generated         Keep the contents of files detected as generated or minified
+  -q, --quiet                 Only report errors
+  -v, --verbose               Report progress; repeat (-vv) to list every skipped or
+                              lossily decoded file (RUST_LOG overrides both)
+  --compress <gzip|zstd>      Write a compressed output file (.gz / .zst)
+  --clipboard                 Copy the generated document to the system clipboard
+  --no-file                   Do not write the output file (with --clipboard)
+  --no-clobber                Fail instead of overwriting an existing output file
+  --timestamp                 Append a timestamp to the output file name, e.g. repo-20240511T143210.md
+  --incremental               Reuse sections of unchanged files from the previous output (markdown/text only)
+  --report <file>             Save every skipped, shortened or unreadable file as JSON
+  --manifest <file>           Save a JSON index of the document: every file with the byte offset
+                              and length of its section, and the skipped files
+  --strict                    Exit with code 8 if files were unreadable, binary, not valid
+                              UTF-8, left unfiltered or omitted at the size limit
+  --audit                     Report likely secrets, .env files and large lockfiles on stderr
+  --fail-on-audit             Like --audit, but exit with code 9 without writing output on findings
+  --strict-on <list>          Like --strict, for the given categories (e.g. unreadable,non_utf8)
+  --ssh-key <file>            Private key for SSH remotes (adds -i <file> -o IdentitiesOnly=yes)
+  --ssh-strict <policy>       Host key checking for SSH remotes: yes, no or accept-new
+                              (default: accept-new, or GIT_SSH_COMMAND unchanged if set)
+  --token <token>             Access token for private HTTPS remotes
+                              (or GIT_CONCAT_TOKEN / GITHUB_TOKEN)";
+
+/// Compression applied to the output file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Parses a compression name given on the command line
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "gzip" | "gz" => Ok(Self::Gzip),
+            "zstd" | "zst" => Ok(Self::Zstd),
+            _ => Err(format!("Unknown compression '{}' (expected gzip or zstd)", name)),
+        }
     }
 
-    /// Generates the complete markdown document for the repository
-    fn generate_markdown(&self, repo_path: &str) -> Result<String, Box<dyn Error>> {
-        // Handle both local paths and remote repositories
-        let temp_dir;
-        let repo_dir = if repo_path.starts_with("http") || repo_path.starts_with("git@") || repo_path.starts_with("ssh://") {
-            // Clone remote repository to temporary directory
-            temp_dir = TempDir::new()?;
-            println!("Cloning repository to {:?}...", temp_dir.path());
+    /// Extension appended to the output file name
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gz",
+            Self::Zstd => "zst",
+        }
+    }
+}
 
-            // Build git command with appropriate flags
-            let mut git_cmd = Command::new("git");
-            git_cmd.args(&["clone"]);
+/// How files and directories starting with `.` are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HiddenPolicy {
+    Default, // Include dotfiles, except editor/tool directories like .idea
+    Include, // Include all dotfiles and dot-directories
+    Exclude, // Exclude all dotfiles and dot-directories
+}
 
-            // Add SSH specific flags if using SSH
-            if repo_path.starts_with("git@") || repo_path.starts_with("ssh://") {
-                git_cmd.args(&["-c", "core.sshCommand=ssh -o StrictHostKeyChecking=accept-new"]);
-            }
+/// What to do when the output would exceed `--max-output-size`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OnLimit {
+    Abort,    // Fail without writing anything (default)
+    Truncate, // Stop adding files and list the omitted ones
+}
 
-            // Add repository URL and target directory
-            git_cmd.args(&[repo_path, &temp_dir.path().to_string_lossy()]);
+impl OnLimit {
+    /// Parses a limit action given on the command line
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "abort" => Ok(Self::Abort),
+            "truncate" => Ok(Self::Truncate),
+            _ => Err(format!("Unknown --on-limit action '{}' (expected abort or truncate)", name)),
+        }
+    }
+}
+
+/// Order of the file sections in the contents (`--order`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContentOrder {
+    Path,     // Structure order (default)
+    Priority, // Documentation and manifests first, see `priority_tier`
+}
+
+impl ContentOrder {
+    /// Parses an order given on the command line
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "path" => Ok(Self::Path),
+            "priority" => Ok(Self::Priority),
+            _ => Err(format!("Unknown --order '{}' (expected path or priority)", name)),
+        }
+    }
+}
+
+/// Line endings of text file contents in the output (`--normalize-eol`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineEndings {
+    Keep, // Leave contents as they are (--preserve-eol)
+    Lf,   // Convert CRLF to LF (default)
+    Crlf, // Convert LF to CRLF
+}
 
-            // Execute the command
-            let output = git_cmd.output()?;
+impl LineEndings {
+    /// Parses a line ending style given on the command line
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "keep" => Ok(Self::Keep),
+            "lf" => Ok(Self::Lf),
+            "crlf" => Ok(Self::Crlf),
+            _ => Err(format!("Unknown --normalize-eol style '{}' (expected lf, crlf or keep)", name)),
+        }
+    }
 
-            // Print any error messages from git
-            if !output.stderr.is_empty() {
-                eprintln!("Git output: {}", String::from_utf8_lossy(&output.stderr));
+    /// Converts the line endings of `text`; lone `\r` characters (classic Mac line endings) are left alone.
+    /// Returns None when nothing needed converting.
+    fn apply(&self, text: &str) -> Option<String> {
+        match self {
+            Self::Keep => None,
+            Self::Lf => text.contains("\r\n").then(|| text.replace("\r\n", "\n")),
+            Self::Crlf => {
+                let bare = text.match_indices('\n').any(|(i, _)| !text[..i].ends_with('\r'));
+                bare.then(|| {
+                    let mut converted = String::with_capacity(text.len() + text.len() / 32);
+                    for line in text.split_inclusive('\n') {
+                        match line.strip_suffix('\n') {
+                            Some(line) => {
+                                converted.push_str(line.strip_suffix('\r').unwrap_or(line));
+                                converted.push_str("\r\n");
+                            }
+                            None => converted.push_str(line),
+                        }
+                    }
+                    converted
+                })
             }
-            temp_dir.path().to_path_buf()
-        } else {
-            PathBuf::from(repo_path)
-        };
+        }
+    }
+}
 
-        // Generate repository structure
-        let structure = self.get_file_structure(&repo_dir, Path::new(""))?;
+/// Size or line limit of a `--limit` rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Limit {
+    Bytes(u64),   // e.g. `10K`
+    Lines(usize), // e.g. `50lines`
+}
+
+/// A limit for the files matching a pattern (`--limit <pattern>=<limit>`)
+#[derive(Debug, Clone)]
+struct LimitRule {
+    rule: String,                 // The rule as given, named in the placeholder of omitted files
+    matcher: globset::GlobMatcher, // Matches relative paths
+    limit: Limit,
+}
 
-        // Create markdown document
-        let mut markdown = String::from("# Repository Structure\n\n```json\n");
-        markdown.push_str(&serde_json::to_string_pretty(&structure)?);
-        markdown.push_str("\n```\n\n# File Contents\n\n");
+impl LimitRule {
+    /// Parses `<pattern>=<limit>`, where the limit is a size (`10K`, `1M`) or a line count (`50lines`)
+    fn parse(rule: &str) -> Result<Self, String> {
+        let invalid = |reason: String| format!("Invalid --limit '{}' ({})", rule, reason);
+        let (pattern, value) = rule
+            .rsplit_once('=')
+            .filter(|(pattern, value)| !pattern.is_empty() && !value.is_empty())
+            .ok_or_else(|| invalid("expected pattern=size or pattern=<n>lines".to_string()))?;
+        let matcher = globset::Glob::new(pattern).map_err(|e| invalid(e.to_string()))?.compile_matcher();
+        let value = value.trim();
+        let limit = match value.strip_suffix("lines").or_else(|| value.strip_suffix("line")) {
+            Some(lines) => Limit::Lines(lines.trim().parse().map_err(|_| invalid("expected a line count".to_string()))?),
+            None => Limit::Bytes(parse_size(value).map_err(invalid)?),
+        };
+        Ok(Self { rule: rule.to_string(), matcher, limit })
+    }
 
-        // Process all files and add their contents to the markdown
-        self.process_files(&structure, &repo_dir, &mut markdown)?;
+    /// The first rule whose pattern matches `path`; later rules for the same file never apply
+    fn first_match<'a>(rules: &'a [Self], path: &str) -> Option<&'a Self> {
+        rules.iter().find(|rule| rule.matcher.is_match(path))
+    }
 
-        Ok(markdown)
+    /// Describes how a file exceeds the limit, e.g. `600 lines`, or None when it doesn't
+    fn exceeded(&self, size: u64, lines: usize) -> Option<String> {
+        match self.limit {
+            Limit::Bytes(max) => (size > max).then(|| format!("{} bytes", size)),
+            Limit::Lines(max) => (lines > max).then(|| format!("{} lines", lines)),
+        }
     }
+}
 
-    /// Recursively processes files and adds their contents to the markdown document
-    fn process_files(&self, entries: &[FileEntry], base_dir: &Path, markdown: &mut String) -> Result<(), Box<dyn Error>> {
-        for entry in entries {
-            if entry.entry_type == "directory" {
-                // Recursively process directory contents
-                if let Some(ref children) = entry.children {
-                    self.process_files(children, base_dir, markdown)?;
-                }
-            } else {
-                // Process file contents
-                let full_path = base_dir.join(&entry.path);
+/// Explicit choice between reading a local checkout and cloning (overrides URL detection)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RepoSource {
+    Local,  // Read the path in place (--local)
+    Remote, // Clone it first (--remote)
+}
+
+/// Default for `--max-output-size` (500 MB)
+const DEFAULT_MAX_OUTPUT_SIZE: u64 = 500 * 1024 * 1024;
+
+/// Options collected from the command line
+#[derive(Debug)]
+struct Config {
+    repo_path: String,         // Local path or remote URL of the repository
+    paths: Vec<String>,        // Only include these files and directories (empty = everything)
+    branch: Option<String>,    // Branch to clone (from a `/tree/<branch>` URL)
+    format: OutputFormat,      // Output format to generate
+    embed_binary: Option<u64>, // Embed binary files up to this size as base64
+    embed_images: bool,        // Inline small images as data URIs
+    dedup: bool,               // Collapse files with identical contents
+    hash: bool,                // Add each file's SHA-256 to the structure
+    token: Option<String>,     // Access token for private HTTPS remotes
+    ssh_key: Option<PathBuf>,  // Private key for SSH remotes
+    ssh_strict: Option<String>, // StrictHostKeyChecking policy for SSH remotes
+    keep_empty_dirs: bool,     // Keep directories with no included children
+    front_matter: bool,        // Prepend YAML front matter with document metadata
+    stats: bool,               // Emit a per-language statistics table
+    toc: bool,                 // Emit a table of contents linking to the file sections
+    log_level: log::LevelFilter, // How much to report while running
+    skip_generated: bool,      // Skip files marked linguist-generated
+    include_generated: bool,   // Keep the contents of files detected as generated or minified
+    structure_fields: Option<Vec<StructureField>>, // Fields serialized in the structure (None = all)
+    structure_only: bool,      // Only emit the structure, without reading file contents
+    no_structure: bool,        // Leave out the structure section
+    interactive: bool,         // Choose files from a terminal checklist
+    selection: Option<PathBuf>, // Saved selection to apply
+    save_selection: Option<PathBuf>, // Where to save the chosen selection
+    clipboard: bool,           // Copy the document to the system clipboard
+    no_clobber: bool,          // Refuse to overwrite an existing output file
+    report: Option<PathBuf>,   // Where to save the JSON run report
+    manifest: Option<PathBuf>, // Where to save the sidecar manifest of the document
+    audit: bool,               // Report risky files (secrets, .env files, large lockfiles)
+    fail_on_audit: bool,       // Fail without writing output when the audit finds anything
+    strict: Option<Vec<Reason>>, // Report categories that make the run fail
+    timestamp: bool,           // Append a timestamp to the output file name
+    incremental: bool,         // Reuse unchanged sections of the previous output
+    no_file: bool,             // Skip writing the output file
+    recurse_submodules: bool,  // Include the contents of git submodules
+    git_ref: Option<String>,   // Git ref of a local repository to read, through a temporary worktree
+    since: Option<String>,     // Only include files changed since this git ref
+    modified_since: Option<i64>, // Only include the contents of files last modified after this time (Unix seconds)
+    compress: Option<Compression>, // Compression applied to the output file
+    show_mode: bool,           // Include Unix file permissions in the structure
+    concatignore: Option<PathBuf>, // Extra exclusion patterns file
+    patterns: Vec<String>,     // Patterns from --exclude and --include (as `!pattern`), in order
+    preset: Option<&'static Preset>, // Built-in exclusion profile
+    show_ignores: bool,        // Print the effective ignore rules instead of generating output
+    lang_map: Vec<(String, String)>, // Language overrides (extension or wildcard -> language)
+    hidden: HiddenPolicy,      // How dotfiles and dot-directories are handled
+    include_env: bool,         // Include .env* files
+    max_depth: Option<usize>,  // Maximum directory depth to descend into
+    max_lines: Option<usize>,  // Omit the contents of files with more lines than this
+    limits: Vec<LimitRule>,    // Per-pattern limits, in order (the first match wins over max_lines)
+    preview_lines: Option<usize>, // Keep only this many lines at the start and end of longer files
+    top: Option<usize>,        // Print this many of the largest directories and files
+    max_output_size: Option<u64>, // Maximum size of the generated document (None = unlimited)
+    wrap: Option<usize>,       // Hard-wrap content lines longer than this many characters
+    filter_cmd: Option<String>, // Shell command each text file is piped through
+    path_prefix: Option<String>, // Prepended to every emitted path (ends with '/')
+    flatten_paths: bool,       // Show only file names in markdown headings
+    split: Option<u64>,        // Split the output into parts of at most this many bytes
+    lang: Option<Vec<String>>, // Only include files of these languages
+    source: Option<RepoSource>, // Forced local/remote handling (None = detect from the argument)
+    no_cache: bool,            // Clone remotes into a temporary directory instead of the cache
+    refresh_cache: bool,       // Replace the cached clone with a fresh one
+    cache_dir: Option<PathBuf>, // Clone cache location (None = default)
+    clear_cache: bool,         // Delete the clone cache before running
+    via_api: bool,             // Fetch github.com repositories through the API instead of cloning
+    exclude_lang: Vec<String>, // Exclude files of these languages
+    only_ext: Option<HashSet<String>>, // Only include files with these extensions ("" = none)
+    on_limit: OnLimit,         // What to do when the size limit is reached
+    normalize_eol: LineEndings, // Line endings of text file contents
+    order: ContentOrder,       // Order of the file sections
+}
 
-                // Try to read the file content, handle non-UTF8 files
-                let content = match fs::read_to_string(&full_path) {
-                    Ok(content) => content,
-                    Err(e) => {
-                        eprintln!("Warning: Unable to read {} as UTF-8 text: {}", entry.path, e);
-                        String::from("[Binary or non-UTF8 file content skipped]")
+impl Config {
+    /// Parses command line arguments (excluding the program name)
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut repo_path = None;
+        let mut paths = Vec::new();
+        let mut format = OutputFormat::Markdown;
+        let mut embed_binary = None;
+        let mut embed_images = false;
+        let mut dedup = false;
+        let mut hash = false;
+        let mut token = None;
+        let mut ssh_key = None;
+        let mut ssh_strict = None;
+        let mut keep_empty_dirs = false;
+        let mut front_matter = false;
+        let mut stats = false;
+        let mut toc = false;
+        let mut quiet = false;
+        let mut verbose = 0;
+        let mut skip_generated = false;
+        let mut include_generated = false;
+        let mut structure_fields = None;
+        let mut structure_only = false;
+        let mut no_structure = false;
+        let mut interactive = false;
+        let mut selection = None;
+        let mut save_selection = None;
+        let mut clipboard = false;
+        let mut no_file = false;
+        let mut no_clobber = false;
+        let mut report = None;
+        let mut manifest = None;
+        let mut audit = false;
+        let mut fail_on_audit = false;
+        let mut strict = None;
+        let mut timestamp = false;
+        let mut incremental = false;
+        let mut recurse_submodules = false;
+        let mut git_ref = None;
+        let mut since = None;
+        let mut modified_since = None;
+        let mut compress = None;
+        let mut show_mode = false;
+        let mut concatignore = None;
+        let mut patterns = Vec::new();
+        let mut preset = None;
+        let mut show_ignores = false;
+        let mut lang_map = Vec::new();
+        let mut hidden = HiddenPolicy::Default;
+        let mut include_env = false;
+        let mut max_depth = None;
+        let mut max_lines = None;
+        let mut limits = Vec::new();
+        let mut config_file = None;
+        let mut preview_lines = None;
+        let mut top = None;
+        let mut max_output_size = Some(DEFAULT_MAX_OUTPUT_SIZE);
+        let mut on_limit = OnLimit::Abort;
+        let mut normalize_eol = None;
+        let mut preserve_eol = false;
+        let mut order = ContentOrder::Path;
+        let mut wrap = None;
+        let mut filter_cmd = None;
+        let mut path_prefix = None;
+        let mut flatten_paths = false;
+        let mut split = None;
+        let mut lang: Option<Vec<String>> = None;
+        let mut exclude_lang = Vec::new();
+        let mut only_ext: Option<HashSet<String>> = None;
+        let mut source = None;
+        let mut no_cache = false;
+        let mut refresh_cache = false;
+        let mut cache_dir = None;
+        let mut clear_cache = false;
+        let mut via_api = false;
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--format" => {
+                    let value = iter.next().ok_or("--format requires a value")?;
+                    format = OutputFormat::parse(value)?;
+                }
+                "--embed-binary" => {
+                    let value = iter.next().ok_or("--embed-binary requires a size")?;
+                    embed_binary = Some(parse_size(value)?);
+                }
+                "--embed-images" => embed_images = true,
+                "--dedup" => dedup = true,
+                "--hash" => hash = true,
+                "--keep-empty-dirs" => keep_empty_dirs = true,
+                "--front-matter" => front_matter = true,
+                "--stats" => stats = true,
+                "--toc" => toc = true,
+                "--structure-only" => structure_only = true,
+                "--no-structure" => no_structure = true,
+                "-q" | "--quiet" => quiet = true,
+                "-v" | "--verbose" => verbose += 1,
+                "-vv" => verbose += 2,
+                "--skip-generated" => skip_generated = true,
+                "--include-generated" => include_generated = true,
+                "--concatignore" => {
+                    let value = iter.next().ok_or("--concatignore requires a file")?;
+                    if !Path::new(value).is_file() {
+                        return Err(format!("Concatignore file '{}' does not exist", value));
                     }
-                };
+                    concatignore = Some(PathBuf::from(value));
+                }
+                "--exclude" => {
+                    let value = iter.next().ok_or("--exclude requires a pattern")?;
+                    patterns.push(value.clone());
+                }
+                "--include" => {
+                    let value = iter.next().ok_or("--include requires a pattern")?;
+                    patterns.push(format!("!{}", value));
+                }
+                "--preset" => {
+                    let value = iter.next().ok_or("--preset requires a name")?;
+                    preset = Some(Preset::find(value)?);
+                }
+                "--show-ignores" => show_ignores = true,
+                "--show-mode" => show_mode = true,
+                "--hidden" | "--include-hidden" => hidden = HiddenPolicy::Include,
+                "--no-hidden" => hidden = HiddenPolicy::Exclude,
+                "--include-env" => include_env = true,
+                "--max-lines" => {
+                    let value = iter.next().ok_or("--max-lines requires a number")?;
+                    max_lines = Some(
+                        value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|&lines| lines > 0)
+                            .ok_or_else(|| format!("Invalid --max-lines '{}' (expected a positive number)", value))?,
+                    );
+                }
+                "--limit" => {
+                    let value = iter.next().ok_or("--limit requires pattern=size or pattern=<n>lines")?;
+                    limits.push(LimitRule::parse(value)?);
+                }
+                "--config" => config_file = Some(PathBuf::from(iter.next().ok_or("--config requires a file path")?)),
+                "--preview-lines" => {
+                    let value = iter.next().ok_or("--preview-lines requires a number")?;
+                    preview_lines = Some(
+                        value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|&lines| lines > 0)
+                            .ok_or_else(|| format!("Invalid --preview-lines '{}' (expected a positive number)", value))?,
+                    );
+                }
+                "--top" => {
+                    let value = iter.next().ok_or("--top requires a number")?;
+                    top = Some(
+                        value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|&count| count > 0)
+                            .ok_or_else(|| format!("Invalid --top '{}' (expected a positive number)", value))?,
+                    );
+                }
+                "--max-depth" => {
+                    let value = iter.next().ok_or("--max-depth requires a number")?;
+                    max_depth = Some(
+                        value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|&depth| depth > 0)
+                            .ok_or_else(|| format!("Invalid --max-depth '{}' (expected a positive number)", value))?,
+                    );
+                }
+                "--max-output-size" => {
+                    let value = iter.next().ok_or("--max-output-size requires a size")?;
+                    // 0 or `none` disables the limit
+                    max_output_size = match value.as_str() {
+                        "none" => None,
+                        _ => Some(parse_size(value)?).filter(|&size| size > 0),
+                    };
+                }
+                "--flatten-paths" => flatten_paths = true,
+                "--path-prefix" => {
+                    let value = iter.next().ok_or("--path-prefix requires a value")?;
+                    let prefix = value.trim_end_matches('/');
+                    path_prefix = (!prefix.is_empty()).then(|| format!("{}/", prefix));
+                }
+                "--filter-cmd" => {
+                    let value = iter.next().ok_or("--filter-cmd requires a command")?;
+                    filter_cmd = Some(value.clone());
+                }
+                "--wrap" => {
+                    let value = iter.next().ok_or("--wrap requires a line length")?;
+                    wrap = Some(
+                        value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|&width| width > 0)
+                            .ok_or_else(|| format!("Invalid --wrap '{}' (expected a positive number)", value))?,
+                    );
+                }
+                "--split" => {
+                    let value = iter.next().ok_or("--split requires a size")?;
+                    let size = parse_size(value)?;
+                    if size <= SPLIT_HEADER_RESERVE {
+                        return Err(format!("--split size must be larger than {} bytes", SPLIT_HEADER_RESERVE));
+                    }
+                    split = Some(size);
+                }
+                "--on-limit" => {
+                    let value = iter.next().ok_or("--on-limit requires abort or truncate")?;
+                    on_limit = OnLimit::parse(value)?;
+                }
+                "--normalize-eol" => {
+                    let value = iter.next().ok_or("--normalize-eol requires lf, crlf or keep")?;
+                    normalize_eol = Some(LineEndings::parse(value)?);
+                }
+                "--preserve-eol" => preserve_eol = true,
+                "--order" => {
+                    let value = iter.next().ok_or("--order requires path or priority")?;
+                    order = ContentOrder::parse(value)?;
+                }
+                "--lang" => {
+                    let value = iter.next().ok_or("--lang requires a list of languages")?;
+                    lang.get_or_insert_with(Vec::new).extend(parse_language_list(value));
+                }
+                "--exclude-lang" => {
+                    let value = iter.next().ok_or("--exclude-lang requires a list of languages")?;
+                    exclude_lang.extend(parse_language_list(value));
+                }
+                "--only-ext" => {
+                    let value = iter.next().ok_or("--only-ext requires an extension")?;
+                    only_ext.get_or_insert_with(HashSet::new).insert(normalize_extension(value));
+                }
+                _ if arg.starts_with("--only-ext=") => {
+                    let value = &arg["--only-ext=".len()..];
+                    only_ext.get_or_insert_with(HashSet::new).insert(normalize_extension(value));
+                }
+                "--lang-map" => {
+                    let value = iter.next().ok_or("--lang-map requires ext=language")?;
+                    let (key, language) = value
+                        .split_once('=')
+                        .filter(|(key, language)| !key.is_empty() && !language.is_empty())
+                        .ok_or_else(|| format!("Invalid --lang-map '{}' (expected ext=language)", value))?;
+                    if is_glob(key) {
+                        globset::Glob::new(key).map_err(|e| format!("Invalid --lang-map pattern '{}': {}", key, e))?;
+                    }
+                    lang_map.push((key.to_string(), language.to_string()));
+                }
+                "--interactive" => interactive = true,
+                "--compress" => {
+                    let value = iter.next().ok_or("--compress requires gzip or zstd")?;
+                    compress = Some(Compression::parse(value)?);
+                }
+                "--clipboard" => clipboard = true,
+                "--no-file" => no_file = true,
+                "--no-clobber" => no_clobber = true,
+                "--report" => {
+                    let value = iter.next().ok_or("--report requires a file path")?;
+                    report = Some(PathBuf::from(value));
+                }
+                "--audit" => audit = true,
+                "--fail-on-audit" => {
+                    audit = true;
+                    fail_on_audit = true;
+                }
+                "--manifest" => {
+                    let value = iter.next().ok_or("--manifest requires a file path")?;
+                    manifest = Some(PathBuf::from(value));
+                }
+                "--strict" => {
+                    strict.get_or_insert_with(|| Reason::ALL.into_iter().filter(Reason::is_warning).collect());
+                }
+                "--strict-on" => {
+                    let value = iter.next().ok_or("--strict-on requires a list of categories")?;
+                    strict = Some(Reason::parse_list(value)?);
+                }
+                "--timestamp" => timestamp = true,
+                "--incremental" => incremental = true,
+                "--recurse-submodules" => recurse_submodules = true,
+                "--local" | "--remote" => {
+                    let requested = if arg == "--local" { RepoSource::Local } else { RepoSource::Remote };
+                    if source.is_some_and(|source| source != requested) {
+                        return Err("--local and --remote cannot be combined".to_string());
+                    }
+                    source = Some(requested);
+                }
+                "--no-cache" => no_cache = true,
+                "--refresh-cache" | "--refresh" => refresh_cache = true,
+                "--cache-dir" => cache_dir = Some(PathBuf::from(iter.next().ok_or("--cache-dir requires a directory")?)),
+                "--clear-cache" => clear_cache = true,
+                "--via-api" => via_api = true,
+                "--ref" => git_ref = Some(iter.next().ok_or("--ref requires a git ref")?.clone()),
+                "--since" => {
+                    let value = iter.next().ok_or("--since requires a git ref")?;
+                    // git would read a leading dash as one of its own options
+                    if value.starts_with('-') {
+                        return Err(format!("Invalid --since '{}' (expected a git ref)", value));
+                    }
+                    since = Some(value.clone());
+                }
+                "--modified-since" => {
+                    let value = iter.next().ok_or("--modified-since requires a date or duration")?;
+                    modified_since = Some(parse_cutoff(value, chrono::Utc::now().timestamp())?);
+                }
+                "--selection" => {
+                    selection = Some(PathBuf::from(iter.next().ok_or("--selection requires a file")?));
+                }
+                "--save-selection" => {
+                    save_selection = Some(PathBuf::from(iter.next().ok_or("--save-selection requires a file")?));
+                }
+                "--structure-fields" => {
+                    let value = iter.next().ok_or("--structure-fields requires a list of fields")?;
+                    structure_fields = Some(StructureField::parse_list(value)?);
+                }
+                "--path" => {
+                    let value = iter.next().ok_or("--path requires a path")?;
+                    let path = normalize_path_filter(value);
+                    if path.is_empty() || path.split('/').any(|part| part == "..") {
+                        return Err(format!("Invalid path filter '{}' (expected a path inside the repository)", value));
+                    }
+                    paths.push(path);
+                }
+                "--ssh-key" => {
+                    let value = iter.next().ok_or("--ssh-key requires a file")?;
+                    if !Path::new(value).is_file() {
+                        return Err(format!("SSH key '{}' does not exist", value));
+                    }
+                    ssh_key = Some(PathBuf::from(value));
+                }
+                "--ssh-strict" => {
+                    let value = iter.next().ok_or("--ssh-strict requires yes, no or accept-new")?;
+                    if !matches!(value.as_str(), "yes" | "no" | "accept-new") {
+                        return Err(format!("Invalid --ssh-strict '{}' (expected yes, no or accept-new)", value));
+                    }
+                    ssh_strict = Some(value.clone());
+                }
+                "--token" => {
+                    token = Some(iter.next().ok_or("--token requires a value")?.clone());
+                }
+                _ if arg.starts_with("--") => return Err(format!("Unknown option '{}'", arg)),
+                _ if repo_path.is_none() => repo_path = Some(arg.clone()),
+                // Further positional arguments restrict the output to these files or directories
+                _ => {
+                    let path = normalize_path_filter(arg);
+                    if path.is_empty() || path.split('/').any(|part| part == "..") {
+                        return Err(format!("Invalid path filter '{}' (expected a path inside the repository)", arg));
+                    }
+                    paths.push(path);
+                }
+            }
+        }
 
-                // Add file header and content to markdown
-                markdown.push_str(&format!("## {}\n\n", entry.path));
-                let lang = self.get_language_from_ext(&full_path);
-                markdown.push_str(&format!("```{}\n", lang));
-                markdown.push_str(&content);
-                markdown.push_str("\n```\n\n");
+        if no_file && !clipboard {
+            return Err("--no-file requires --clipboard".to_string());
+        }
+        if split.is_some() && (format != OutputFormat::Markdown || clipboard) {
+            return Err("--split only works with markdown file output (not with jsonl or --clipboard)".to_string());
+        }
+        if embed_images && format != OutputFormat::Markdown {
+            return Err("--embed-images only works with markdown output".to_string());
+        }
+        if split.is_some() && manifest.is_some() {
+            return Err("--manifest describes a single document and cannot be combined with --split".to_string());
+        }
+        if structure_only && no_structure {
+            return Err("--structure-only and --no-structure cannot be combined".to_string());
+        }
+        if structure_only && format == OutputFormat::Jsonl {
+            return Err("--structure-only does not work with --format jsonl (use --format json)".to_string());
+        }
+        if format == OutputFormat::Json && !structure_only {
+            return Err("--format json requires --structure-only".to_string());
+        }
+        if structure_only && (stats || hash) {
+            return Err("--stats and --hash need file contents and cannot be combined with --structure-only".to_string());
+        }
+        if toc && (format != OutputFormat::Markdown || structure_only) {
+            return Err("--toc only works with markdown output of file contents".to_string());
+        }
+        if incremental && !matches!(format, OutputFormat::Markdown | OutputFormat::Text) {
+            return Err("--incremental only works with markdown or text output".to_string());
+        }
+        if incremental && (no_file || split.is_some() || compress.is_some() || dedup || structure_only) {
+            return Err("--incremental cannot be combined with --no-file, --split, --compress, --dedup or --structure-only".to_string());
+        }
+        // Limits from the options file come after the command line ones, so --limit rules win
+        if let Some(path) = &config_file {
+            for rule in ConfigFile::load(path)?.limits {
+                limits.push(LimitRule::parse(&rule)?);
             }
         }
-        Ok(())
+        let normalize_eol = match normalize_eol {
+            Some(style) if preserve_eol && style != LineEndings::Keep => {
+                return Err("--preserve-eol cannot be combined with --normalize-eol lf or crlf".to_string());
+            }
+            _ if preserve_eol => LineEndings::Keep,
+            style => style.unwrap_or(LineEndings::Lf),
+        };
+        if incremental && (timestamp || no_clobber) {
+            return Err("--incremental updates the output in place and cannot be combined with --timestamp or --no-clobber".to_string());
+        }
+        if audit && incremental {
+            return Err("--audit reads every file and cannot be combined with --incremental".to_string());
+        }
+        if via_api && (since.is_some() || modified_since.is_some() || recurse_submodules) {
+            return Err("--via-api has no git history or submodule contents and cannot be combined with --since, --modified-since or --recurse-submodules".to_string());
+        }
+
+        // --clear-cache on its own just wipes the cache
+        let mut repo_path = match repo_path {
+            Some(repo_path) => repo_path,
+            None if clear_cache => String::new(),
+            None => return Err("Missing repository path or URL".to_string()),
+        };
+
+        // A link to a directory in the web UI clones the repository and filters to that directory
+        let mut branch = None;
+        if let Some((url, tree_branch, path)) = split_tree_url(&repo_path) {
+            repo_path = url;
+            branch = Some(tree_branch);
+            paths.extend(path);
+        }
+
+        // Expand `~` and canonicalize local paths, drop query strings from URLs
+        if !repo_path.is_empty() {
+            let remote = source.map_or_else(|| location::is_remote(&repo_path), |source| source == RepoSource::Remote);
+            if remote && git_ref.is_some() {
+                return Err("--ref only applies to local repositories (link to /tree/<branch> for remotes)".to_string());
+            }
+            repo_path = location::normalize(&repo_path, remote);
+        }
+
+        Ok(Self {
+            repo_path,
+            paths,
+            branch,
+            format,
+            embed_binary,
+            embed_images,
+            dedup,
+            hash,
+            // Fall back to the token from the environment (useful in CI)
+            token: token.or_else(|| {
+                ["GIT_CONCAT_TOKEN", "GITHUB_TOKEN"]
+                    .into_iter()
+                    .find_map(|name| std::env::var(name).ok().filter(|t| !t.is_empty()))
+            }),
+            ssh_key,
+            ssh_strict,
+            keep_empty_dirs,
+            front_matter,
+            stats,
+            toc,
+            log_level: logging::level_from_flags(quiet, verbose),
+            skip_generated,
+            include_generated,
+            structure_fields,
+            structure_only,
+            no_structure,
+            interactive,
+            selection,
+            save_selection,
+            clipboard,
+            no_file,
+            no_clobber,
+            report,
+            manifest,
+            audit,
+            fail_on_audit,
+            strict,
+            timestamp,
+            incremental,
+            recurse_submodules,
+            git_ref,
+            since,
+            modified_since,
+            compress,
+            show_mode,
+            concatignore,
+            patterns,
+            preset,
+            show_ignores,
+            lang_map,
+            hidden,
+            include_env,
+            max_depth,
+            // An explicit --max-lines wins over the preset's
+            max_lines: max_lines.or(preset.and_then(|preset| preset.max_lines)),
+            limits,
+            preview_lines,
+            top,
+            max_output_size,
+            on_limit,
+            normalize_eol,
+            order,
+            wrap,
+            filter_cmd,
+            path_prefix,
+            flatten_paths,
+            split,
+            lang,
+            exclude_lang,
+            only_ext,
+            source,
+            no_cache,
+            refresh_cache,
+            cache_dir,
+            clear_cache,
+            via_api,
+        })
     }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    // Get command line arguments
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <repository-path-or-url>", args[0]);
-        std::process::exit(1);
-    }
+/// Normalizes a path filter argument to the form used in `FileEntry::path` (`./src/` -> `src`)
+fn normalize_path_filter(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
 
-    // Create output directory if it doesn't exist
-    fs::create_dir_all("./output")?;
+/// Normalizes an extension given on the command line: `.RS` -> `rs`
+fn normalize_extension(ext: &str) -> String {
+    ext.trim().trim_start_matches('.').to_lowercase()
+}
 
-    // Extract repository name from path or URL
-    let repo_name = if args[1].ends_with('/') {
-        args[1].trim_end_matches('/')
-    } else {
-        &args[1]
-    };
+/// Splits a comma-separated language list such as `rust,toml` into lowercase names
+fn parse_language_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
 
-    // Handle different URL formats
-    let repo_name = if repo_name.starts_with("git@") {
-        // SSH format: git@host:user/repo.git
-        repo_name.split(':').last().unwrap_or(repo_name)
+/// Parses a byte size such as `2048`, `16K` or `1M`
+fn parse_size(value: &str) -> Result<u64, String> {
+    let upper = value.trim().to_ascii_uppercase();
+    let digits = upper.trim_end_matches('B');
+    let (number, multiplier) = if let Some(n) = digits.strip_suffix('K') {
+        (n, 1024)
+    } else if let Some(n) = digits.strip_suffix('M') {
+        (n, 1024 * 1024)
+    } else if let Some(n) = digits.strip_suffix('G') {
+        (n, 1024 * 1024 * 1024)
     } else {
-        // HTTPS or local path format
-        repo_name.split('/').last().unwrap_or(repo_name)
+        (digits, 1)
     };
+    let number = number.trim().parse::<u64>().map_err(|_| format!("Invalid size '{}'", value))?;
+    number.checked_mul(multiplier).ok_or_else(|| format!("Size '{}' is too large", value))
+}
 
-    // Clean up the name
-    let repo_name = repo_name
-        .strip_suffix(".git")
-        .unwrap_or(repo_name)
-        .replace(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '_', "-");
-
-    let repo_name = if repo_name.is_empty() {
-        "repository".to_string()
+/// Formats a byte size for people, e.g. `18 KB` or `4.2 MB` (binary units, as accepted by `parse_size`)
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 10.0 {
+        format!("{:.1} {}", value, UNITS[unit])
     } else {
-        repo_name.to_string()
+        format!("{:.0} {}", value, UNITS[unit])
+    }
+}
+
+/// File stem of the output: the repository name, with --timestamp followed by `-<timestamp>`.
+/// A timestamped name that is already `taken` (e.g. two runs within a second) gets `-1`, `-2`, ... appended.
+fn output_stem(repo_name: &str, timestamp: Option<&str>, taken: impl Fn(&str) -> bool) -> String {
+    let Some(timestamp) = timestamp else {
+        return repo_name.to_string();
+    };
+    let stem = format!("{}-{}", repo_name, timestamp);
+    if !taken(&stem) {
+        return stem;
+    }
+    (1..).map(|n| format!("{}-{}", stem, n)).find(|candidate| !taken(candidate)).unwrap_or(stem)
+}
+
+/// With --strict, fails the run if any of the selected report categories occurred
+fn check_strict(config: &Config, report: &Report) -> Result<(), ConcatError> {
+    let summary = config.strict.as_ref().and_then(|reasons| report.summary(|reason| reasons.contains(&reason)));
+    match summary {
+        Some(summary) => Err(ConcatError::Strict(summary)),
+        None => Ok(()),
+    }
+}
+
+/// The error for an output file that --no-clobber keeps from being overwritten
+fn already_exists(path: PathBuf) -> ConcatError {
+    let source = io::Error::new(io::ErrorKind::AlreadyExists, "file already exists (not overwritten with --no-clobber)");
+    ConcatError::OutputWrite { path, source }
+}
+
+/// Suffix of the success message for an output file that replaced an earlier one
+fn replaced_note(replaced: bool) -> &'static str {
+    if replaced {
+        " (replaced existing file)"
+    } else {
+        ""
+    }
+}
+
+/// Derives a file-name friendly repository name from a local path or URL
+fn repo_name_from_path(repo_path: &str) -> String {
+    let repo_name = match location::RemoteUrl::parse(repo_path) {
+        Some(url) => url.repo_name(),
+        None => location::local_name(repo_path).unwrap_or_default(),
+    };
+
+    // Clean up the name
+    let repo_name = repo_name.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '_', "-");
+
+    if repo_name.is_empty() {
+        "repository".to_string()
+    } else {
+        repo_name
+    }
+}
+
+/// Default clone cache location, e.g. `~/.cache/repo-to-ai-context-docs` on Linux
+fn default_cache_dir() -> PathBuf {
+    dirs::cache_dir().unwrap_or_else(std::env::temp_dir).join(env!("CARGO_PKG_NAME"))
+}
+
+/// Prints how much of the repository a partial clone actually fetched
+fn report_partial_clone(repo_dir: &Path) {
+    // Entries outside the sparse checkout are marked `S` (skip-worktree)
+    let Some(files) = git_output(repo_dir, &["ls-files", "-v"]) else {
+        return;
+    };
+    let total = files.lines().count();
+    let checked_out = files.lines().filter(|line| !line.starts_with('S')).count();
+    let downloaded = git_output(repo_dir, &["count-objects", "-v"])
+        .and_then(|stats| {
+            stats.lines().find_map(|line| line.strip_prefix("size-pack: ").and_then(|kib| kib.parse::<u64>().ok()))
+        })
+        .unwrap_or(0);
+    info!("Partial clone: checked out {} of {} files, downloaded {} KiB", checked_out, total, downloaded);
+}
+
+/// Splits a GitHub/GitLab link to a directory (`https://host/owner/repo/tree/<branch>/<path>`)
+/// into the repository URL, the branch and the optional subpath
+fn split_tree_url(url: &str) -> Option<(String, String, Option<String>)> {
+    if !url.starts_with("https://") {
+        return None;
+    }
+    let (repo, rest) = url.split_once("/-/tree/").or_else(|| url.split_once("/tree/"))?;
+    let (branch, path) = match rest.split_once('/') {
+        Some((branch, path)) => (branch, Some(normalize_path_filter(path)).filter(|path| !path.is_empty())),
+        None => (rest, None),
+    };
+    (!branch.is_empty()).then(|| (repo.to_string(), branch.to_string(), path))
+}
+
+/// Cache directory name for a remote URL: the repository name plus a hash of the normalized URL
+fn cache_key(repo_path: &str) -> String {
+    let normalized = repo_path.trim().trim_end_matches('/');
+    let normalized = normalized.strip_suffix(".git").unwrap_or(normalized);
+    format!("{}-{}", repo_name_from_path(repo_path), &sha256_hex(normalized.as_bytes())[..16])
+}
+
+/// Runs a git command in the given directory and returns its trimmed stdout on success
+fn git_output(repo_dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(repo_dir).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
+
+/// Reads submodule paths and URLs from `.gitmodules` at the repository root
+fn load_submodules(repo_dir: &Path) -> HashMap<String, String> {
+    let mut submodules = HashMap::new();
+    let Ok(content) = fs::read_to_string(repo_dir.join(".gitmodules")) else {
+        return submodules;
+    };
+
+    // Each `[submodule "name"]` section has a `path` and a `url` key
+    let (mut path, mut url): (Option<String>, Option<String>) = (None, None);
+    for line in content.lines().map(str::trim).chain(std::iter::once("[end]")) {
+        if line.starts_with('[') {
+            if let (Some(path), Some(url)) = (path.take(), url.take()) {
+                submodules.insert(path.trim_matches('/').to_string(), url);
+            }
+        } else if let Some((key, value)) = line.split_once('=') {
+            match key.trim() {
+                "path" => path = Some(value.trim().to_string()),
+                "url" => url = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+    submodules
+}
+
+/// Formats a file's Unix permission bits as octal (None on platforms without them)
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(format!("{:04o}", metadata.permissions().mode() & 0o7777))
+}
+
+/// Formats a file's Unix permission bits as octal (None on platforms without them)
+#[cfg(not(unix))]
+fn file_mode(_metadata: &fs::Metadata) -> Option<String> {
+    None
+}
+
+/// A directory entry being examined during the structure walk
+enum Walk<'scope, T> {
+    Spawned(thread::ScopedJoinHandle<'scope, T>), // Subdirectory walked on another thread
+    Done(T),                                      // Entry examined inline
+}
+
+/// Checks whether `inner` is the path `outer` or lies below it
+fn is_within(outer: &str, inner: &str) -> bool {
+    inner == outer || (inner.starts_with(outer) && inner.as_bytes().get(outer.len()) == Some(&b'/'))
+}
+
+/// Checks whether a directory has no entries (e.g. an uninitialized submodule)
+fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).map(|mut entries| entries.next().is_none()).unwrap_or(true)
+}
+
+/// Files that differ between a git ref and the working tree
+struct ChangedFiles {
+    changed: Selection,   // Added or modified files (relative to the repository directory)
+    deleted: Vec<String>, // Files deleted since the ref
+    stat: String,         // Output of `git diff --stat`
+}
+
+/// Lists files changed in the working tree since the given git ref
+fn changed_files(repo_dir: &Path, since: &str) -> Result<ChangedFiles, ConcatError> {
+    let git_diff = |args: &[&str]| -> Result<String, ConcatError> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(repo_dir)
+            .args(["-c", "core.quotePath=false", "diff", "--relative"])
+            .args(args)
+            .arg(since)
+            .arg("--")
+            .output()
+            .map_err(|e| ConcatError::io(repo_dir, e))?;
+        if !output.status.success() {
+            return Err(ConcatError::InvalidArguments(format!(
+                "Unable to diff '{}' against '{}': {}",
+                repo_dir.display(),
+                since,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    // Each line is `<status>\t<path>` (renames carry `\t<old>\t<new>`)
+    let mut changed = Vec::new();
+    let mut deleted = Vec::new();
+    for line in git_diff(&["--name-status"])?.lines() {
+        let mut fields = line.split('\t');
+        let status = fields.next().unwrap_or_default();
+        let paths: Vec<&str> = fields.collect();
+        match (status.chars().next(), paths.as_slice()) {
+            (Some('D'), [path]) => deleted.push(path.to_string()),
+            (Some('R'), [old, new]) => {
+                deleted.push(old.to_string());
+                changed.push(new.to_string());
+            }
+            (_, [.., path]) => changed.push(path.to_string()),
+            _ => {}
+        }
+    }
+
+    Ok(ChangedFiles {
+        changed: Selection { files: changed },
+        deleted,
+        stat: git_diff(&["--stat"])?,
+    })
+}
+
+/// Parses the --modified-since cutoff into Unix seconds: a duration back from `now` (`30d`, `12h`, `2w`), a date
+/// (`2026-01-31`, midnight local time) or a date and time (RFC 3339, or local time without an offset)
+fn parse_cutoff(value: &str, now: i64) -> Result<i64, String> {
+    let invalid = || {
+        format!(
+            "Invalid --modified-since '{}' (expected a duration such as 30d, 12h or 2w, or a date such as 2026-01-31)",
+            value
+        )
+    };
+    if let Some((amount, unit)) = value.char_indices().last().map(|(i, unit)| (&value[..i], unit)) {
+        let seconds = match unit {
+            'h' => Some(3600),
+            'd' => Some(24 * 3600),
+            'w' => Some(7 * 24 * 3600),
+            _ => None,
+        };
+        if let Some(seconds) = seconds {
+            let amount: u32 = amount.parse().map_err(|_| invalid())?;
+            return Ok(now - i64::from(amount) * seconds);
+        }
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.timestamp());
+    }
+    let local = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|date| date.and_hms_opt(0, 0, 0)))
+        .ok_or_else(invalid)?;
+    // Times skipped by a daylight saving change don't exist locally; ambiguous ones take the earlier instant
+    local.and_local_timezone(chrono::Local).earliest().map(|time| time.timestamp()).ok_or_else(invalid)
+}
+
+/// Time of the last commit touching each file in the history of HEAD (Unix seconds), from a single `git log` pass.
+/// Empty when the directory is not a git repository.
+fn commit_times(repo_dir: &Path) -> HashMap<String, i64> {
+    let mut times = HashMap::new();
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_dir)
+        .args(["-c", "core.quotePath=false", "log", "--format=%x00%ct", "--name-only", "--relative", "--no-renames"])
+        .output();
+    let output = match output {
+        Ok(output) if output.status.success() => output,
+        _ => {
+            debug!("No git history in {}, using file modification times", repo_dir.display());
+            return times;
+        }
+    };
+    // Commits come newest first: a `\0<time>` line, then the files it touched
+    let mut time = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(commit_time) = line.strip_prefix('\0') {
+            time = commit_time.parse().ok();
+        } else if let (false, Some(time)) = (line.is_empty(), time) {
+            times.entry(line.to_string()).or_insert(time);
+        }
+    }
+    times
+}
+
+/// Records when each file was last modified and marks the ones older than `cutoff` as stale (--modified-since).
+/// Files without a commit (untracked, or outside a git repository) use their mtime. Returns the number of stale files.
+fn mark_stale(entries: &mut [FileEntry], repo_dir: &Path, times: &HashMap<String, i64>, cutoff: i64) -> usize {
+    let mut stale = 0;
+    for entry in entries {
+        match &mut entry.children {
+            Some(children) => stale += mark_stale(children, repo_dir, times, cutoff),
+            None => {
+                let time = times.get(&entry.path).copied().or_else(|| {
+                    incremental::modified(&repo_dir.join(&entry.path)).map(|nanos| (nanos / 1_000_000_000) as i64)
+                });
+                // Files without a known time (e.g. unreadable ones) are kept
+                let Some(time) = time else { continue };
+                entry.modified = chrono::DateTime::from_timestamp(time, 0)
+                    .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string());
+                if time < cutoff {
+                    entry.stale = Some(true);
+                    stale += 1;
+                }
+            }
+        }
+    }
+    stale
+}
+
+/// Escapes characters with a meaning in markdown inline text, so paths render literally
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '&') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Turns a path into an anchor slug: lowercase letters and digits of any script, other runs become `-`
+fn slugify(path: &str) -> String {
+    let mut slug = String::new();
+    for c in path.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Collects file names that occur more than once in the structure tree
+fn colliding_file_names(entries: &[FileEntry]) -> HashSet<String> {
+    fn count<'a>(entries: &'a [FileEntry], counts: &mut HashMap<&'a str, usize>) {
+        for entry in entries {
+            match &entry.children {
+                Some(children) => count(children, counts),
+                None => *counts.entry(entry.name.as_str()).or_default() += 1,
+            }
+        }
+    }
+    let mut counts = HashMap::new();
+    count(entries, &mut counts);
+    counts.into_iter().filter(|&(_, n)| n > 1).map(|(name, _)| name.to_string()).collect()
+}
+
+/// Counts files and sums their sizes across the structure tree
+fn count_files(entries: &[FileEntry]) -> (usize, u64) {
+    entries.iter().fold((0, 0), |(count, size), entry| match &entry.children {
+        // Truncated directories carry the totals of their hidden contents
+        Some(_) if entry.truncated == Some(true) => {
+            (count + entry.file_count.unwrap_or(0), size + entry.size.unwrap_or(0))
+        }
+        Some(children) => {
+            let (c, s) = count_files(children);
+            (count + c, size + s)
+        }
+        None if entry.stale.is_some() => (count, size),
+        None => (count + 1, size + entry.size.unwrap_or(0)),
+    })
+}
+
+/// Fills in the total size, file count and (once contents were read) lines of every directory, bottom-up,
+/// and returns the totals of `entries`. Truncated directories keep the totals recorded at the depth limit.
+fn roll_up(entries: &mut [FileEntry]) -> (usize, u64, Option<usize>) {
+    entries.iter_mut().fold((0, 0, None), |(count, size, lines), entry| {
+        let (c, s, l) = match &mut entry.children {
+            Some(_) if entry.truncated == Some(true) => (entry.file_count.unwrap_or(0), entry.size.unwrap_or(0), None),
+            Some(_) if entry.entry_type == "submodule" => (0, 0, None),
+            Some(children) => {
+                let (c, s, l) = roll_up(children);
+                entry.file_count = Some(c);
+                entry.size = Some(s);
+                entry.lines = l;
+                (c, s, l)
+            }
+            None if entry.stale.is_some() => (0, 0, None),
+            None => (1, entry.size.unwrap_or(0), entry.lines),
+        };
+        (count + c, size + s, lines.map_or(l, |lines| Some(lines + l.unwrap_or(0))))
+    })
+}
+
+/// Prints the `count` largest directories and files to stderr (--top), to help choosing exclusions
+fn print_top(structure: &[FileEntry], count: usize) {
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    let mut pending: Vec<&FileEntry> = structure.iter().collect();
+    while let Some(entry) = pending.pop() {
+        match &entry.children {
+            Some(children) => {
+                if entry.entry_type == "directory" {
+                    dirs.push(entry);
+                }
+                pending.extend(children);
+            }
+            None if entry.stale.is_some() => {}
+            None => files.push(entry),
+        }
+    }
+    for list in [&mut dirs, &mut files] {
+        list.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        list.truncate(count);
+    }
+
+    eprintln!("Largest directories:");
+    for dir in dirs {
+        let files = dir.file_count.unwrap_or(0);
+        let label = format!("{} {}", files, if files == 1 { "file" } else { "files" });
+        eprintln!("  {:>9}  {:>11}  {}/", format_size(dir.size.unwrap_or(0)), label, dir.path);
+    }
+    eprintln!("Largest files:");
+    for file in files {
+        eprintln!("  {:>9}  {}", format_size(file.size.unwrap_or(0)), file.path);
+    }
+}
+
+/// Renders the statistics section as markdown tables
+fn render_stats(languages: &BTreeMap<String, LanguageStats>, generated: usize, normalized: usize) -> String {
+    let files: usize = languages.values().map(|l| l.files).sum();
+    let bytes: u64 = languages.values().map(|l| l.bytes).sum();
+    let lines: usize = languages.values().map(|l| l.lines).sum();
+
+    let mut section = String::from("# Statistics\n\n| Metric | Value |\n|--------|-------|\n");
+    section.push_str(&format!("| Total files | {} |\n", files));
+    section.push_str(&format!("| Total size (bytes) | {} |\n", bytes));
+    section.push_str(&format!("| Lines of code | {} |\n", lines));
+    section.push_str(&format!("| Generated/minified files | {} |\n", generated));
+    section.push_str(&format!("| Line endings normalized | {} |\n\n", normalized));
+
+    // Largest languages first
+    let mut sorted: Vec<_> = languages.iter().collect();
+    sorted.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(b.0)));
+
+    section.push_str("| Language | Files | Size (bytes) | Lines |\n|----------|-------|--------------|-------|\n");
+    for (language, stats) in sorted {
+        section.push_str(&format!("| {} | {} | {} | {} |\n", language, stats.files, stats.bytes, stats.lines));
+    }
+    section.push('\n');
+    section
+}
+
+/// Quotes a string for a POSIX shell (git runs `GIT_SSH_COMMAND` through one)
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Removes credentials (`user:password@`) from a URL so it can be printed safely
+fn redact_url(repo_path: &str) -> String {
+    let Some((scheme, rest)) = repo_path.split_once("://") else {
+        return repo_path.to_string();
+    };
+    match rest.find('@') {
+        Some(at) if !rest[..at].contains('/') => format!("{}://{}", scheme, &rest[at + 1..]),
+        _ => repo_path.to_string(),
+    }
+}
+
+/// Environment variable the askpass helper reads the token from
+const ASKPASS_TOKEN_VAR: &str = "GIT_CONCAT_ASKPASS_TOKEN";
+
+/// A `GIT_ASKPASS` helper that answers git's credential prompts with the access token.
+/// The token is handed over through the environment, so it never appears on a command line.
+struct Askpass {
+    dir: TempDir, // Directory holding the helper script
+}
+
+impl Askpass {
+    /// Writes the helper script to a private temporary directory
+    fn create() -> Result<Self, ConcatError> {
+        let dir = TempDir::new().map_err(|e| ConcatError::io(&std::env::temp_dir(), e))?;
+        let script = dir.path().join("askpass.sh");
+        let content = format!(
+            "#!/bin/sh\ncase \"$1\" in\n  Username*) echo x-access-token ;;\n  *) printf '%s\\n' \"${}\" ;;\nesac\n",
+            ASKPASS_TOKEN_VAR
+        );
+        fs::write(&script, content).map_err(|e| ConcatError::io(&script, e))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&script, fs::Permissions::from_mode(0o700)).map_err(|e| ConcatError::io(&script, e))?;
+        }
+        Ok(Self { dir })
+    }
+
+    /// Makes a git command authenticate with the token instead of stored credentials or a prompt
+    fn apply(&self, git_cmd: &mut Command, token: &str) {
+        git_cmd
+            .args(["-c", "credential.helper="])
+            .env("GIT_ASKPASS", self.dir.path().join("askpass.sh"))
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env(ASKPASS_TOKEN_VAR, token);
+    }
+}
+
+/// A detached `git worktree` of a local repository at a given ref (--ref), so reading another ref never
+/// touches the user's checkout. The worktree is removed again on drop.
+struct Worktree {
+    repo: PathBuf, // Repository the worktree belongs to
+    dir: TempDir,  // Directory holding the checkout
+}
+
+impl Worktree {
+    /// Checks out `git_ref` into a temporary directory
+    fn add(repo: &Path, git_ref: &str) -> Result<Self, ConcatError> {
+        let dir = TempDir::new().map_err(|e| ConcatError::io(&std::env::temp_dir(), e))?;
+        info!("Checking out {} into worktree {:?}...", git_ref, dir.path());
+        let failed = |stderr: String| ConcatError::CheckoutFailed { git_ref: git_ref.to_string(), stderr };
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["worktree", "add", "--detach"])
+            .arg(dir.path())
+            .arg(git_ref)
+            .output()
+            .map_err(|e| failed(format!("unable to run git: {}", e)))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            // `git worktree` needs git 2.5 or later
+            if stderr.contains("is not a git command") {
+                return Err(failed("git worktree is not available, upgrade git to read another ref".to_string()));
+            }
+            return Err(failed(stderr));
+        }
+        Ok(Self { repo: repo.to_path_buf(), dir })
+    }
+
+    /// Root of the checkout
+    fn path(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        let removed = Command::new("git")
+            .arg("-C")
+            .arg(&self.repo)
+            .args(["worktree", "remove", "--force"])
+            .arg(self.dir.path())
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !removed {
+            // Delete the directory and let git forget about it
+            let _ = fs::remove_dir_all(self.dir.path());
+            let _ = Command::new("git").arg("-C").arg(&self.repo).args(["worktree", "prune"]).output();
+            debug!("Removed worktree {:?} by pruning", self.dir.path());
+        }
+    }
+}
+
+/// Checks whether a pattern uses wildcard syntax (`*`, `?` or `[`)
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// A list of file or directory names; entries with wildcard syntax (see `is_glob`) are matched as glob
+/// patterns against the name, all others exactly
+#[derive(Debug, Default)]
+struct NameSet {
+    literals: HashSet<String>, // Exact names, e.g. `node_modules`
+    patterns: Vec<String>,     // Wildcard entries, e.g. `*.egg-info`
+    globs: globset::GlobSet,   // The wildcard entries, compiled
+}
+
+impl NameSet {
+    /// Sorts the names into literals and patterns
+    fn new<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut set = Self::default();
+        let mut globs = globset::GlobSetBuilder::new();
+        for name in names {
+            if !is_glob(name) {
+                set.literals.insert(name.to_string());
+            } else if let Ok(glob) = globset::Glob::new(name) {
+                globs.add(glob);
+                set.patterns.push(name.to_string());
+            }
+        }
+        set.globs = globs.build().unwrap_or_else(|_| globset::GlobSet::empty());
+        set
+    }
+
+    /// Whether the name is in the list or matches one of its patterns
+    fn contains(&self, name: &str) -> bool {
+        self.literals.contains(name) || (!self.patterns.is_empty() && self.globs.is_match(name))
+    }
+
+    /// All entries, sorted, for display
+    fn sorted(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.literals.iter().chain(&self.patterns).map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Pipes `text` through a shell command run in the repository root and returns its output. The file's
+/// relative path is passed as `$1` (sh only) and in CONCAT_FILE. Errors describe why the command failed.
+fn run_filter(command: &str, repo_dir: &Path, path: &str, text: &str) -> Result<String, String> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command).arg("sh").arg(path);
+        shell
+    };
+    let mut child = shell
+        .current_dir(repo_dir)
+        .env("CONCAT_FILE", path)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    // Feed stdin from another thread, so a command that writes before reading everything can't deadlock
+    let mut stdin = child.stdin.take();
+    let output = thread::scope(|scope| {
+        scope.spawn(|| {
+            use std::io::Write;
+            // A command that doesn't read its input closes the pipe early; that is not an error
+            let _ = stdin.take().map(|mut stdin| stdin.write_all(text.as_bytes()));
+        });
+        child.wait_with_output()
+    })
+    .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => output.status.to_string(),
+            stderr => format!("{}: {}", output.status, stderr),
+        });
+    }
+    String::from_utf8(output.stdout).map_err(|_| "output is not valid UTF-8".to_string())
+}
+
+/// Hard-wraps lines longer than `width` characters, preferring to break after whitespace.
+/// Returns None when no line needed wrapping.
+fn wrap_long_lines(text: &str, width: usize) -> Option<String> {
+    if !text.lines().any(|line| line.chars().count() > width) {
+        return None;
+    }
+
+    let mut wrapped = String::with_capacity(text.len() + text.len() / width);
+    for line in text.split_inclusive('\n') {
+        let (mut rest, ending) = match line.strip_suffix('\n') {
+            Some(line) => (line, "\n"),
+            None => (line, ""),
+        };
+        while rest.chars().count() > width {
+            // Byte offset just past the first `width` characters
+            let limit = rest.char_indices().nth(width).map_or(rest.len(), |(i, _)| i);
+            let split = match rest[..limit].rfind(char::is_whitespace) {
+                Some(space) if space > 0 => space + rest[space..].chars().next().map_or(1, char::len_utf8),
+                _ => limit,
+            };
+            wrapped.push_str(&rest[..split]);
+            wrapped.push('\n');
+            rest = &rest[split..];
+        }
+        wrapped.push_str(rest);
+        wrapped.push_str(ending);
+    }
+    Some(wrapped)
+}
+
+/// Keeps the first and last `keep` lines of a text with more than twice as many, joined by a
+/// `... (n lines omitted) ...` marker. Returns None when the text is short enough to keep in full.
+fn preview_lines(text: &str, keep: usize) -> Option<String> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    if lines.len() <= keep.saturating_mul(2) {
+        return None;
+    }
+    let omitted = lines.len() - keep.saturating_mul(2);
+    let mut preview = lines[..keep].concat();
+    preview.push_str(&format!("... ({} lines omitted) ...\n", omitted));
+    preview.push_str(&lines[lines.len() - keep..].concat());
+    Some(preview)
+}
+
+/// MIME type of an image that can be shown inline, by extension
+fn image_mime_type(filename: &str) -> Option<&'static str> {
+    let ext = Path::new(filename).extension()?.to_string_lossy().to_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "svg" => Some("image/svg+xml"),
+        "bmp" => Some("image/bmp"),
+        "ico" => Some("image/x-icon"),
+        _ => None,
+    }
+}
+
+/// Checks whether content looks binary (contains a NUL byte near the start, like git's heuristic)
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8000).any(|&b| b == 0)
+}
+
+/// File name suffixes of minified or bundled assets
+const MINIFIED_SUFFIXES: &[&str] = &[".min.js", ".min.mjs", ".min.css", ".bundle.js", ".bundle.css"];
+
+/// Markers that code generators put into the header of their output
+const GENERATED_MARKERS: &[&str] = &["@generated", "DO NOT EDIT"];
+
+/// How much of a file the generated/minified heuristic looks at
+const CLASSIFY_SAMPLE: usize = 8 * 1024;
+
+/// Lines at least this long only occur in minified code or embedded data
+const MINIFIED_LINE_LENGTH: usize = 5000;
+
+/// Classifies a file as "generated" (header marker) or "minified" (file name, or very long lines of code)
+fn classify_generated(name: &str, bytes: &[u8]) -> Option<&'static str> {
+    if MINIFIED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+        return Some("minified");
+    }
+
+    let sample = String::from_utf8_lossy(&bytes[..bytes.len().min(CLASSIFY_SAMPLE)]);
+    if sample.lines().take(10).any(|line| GENERATED_MARKERS.iter().any(|marker| line.contains(marker))) {
+        return Some("generated");
+    }
+
+    // A long line alone is not enough: data files such as URL lists have many ordinary lines around it
+    let lines: Vec<usize> = sample.lines().map(str::len).collect();
+    let longest = lines.iter().copied().max().unwrap_or(0);
+    let average = sample.len() / lines.len().max(1);
+    (bytes.len() >= MINIFIED_LINE_LENGTH && longest >= MINIFIED_LINE_LENGTH && average >= 1000)
+        .then_some("minified")
+}
+
+/// Build and dependency manifests, emitted early with `--order priority`
+const MANIFEST_FILES: &[&str] = &[
+    "Cargo.toml", "package.json", "pyproject.toml", "setup.py", "setup.cfg", "Pipfile", "go.mod", "pom.xml",
+    "build.gradle", "build.gradle.kts", "settings.gradle", "Gemfile", "composer.json", "CMakeLists.txt", "Makefile",
+    "meson.build", "mix.exs", "Package.swift", "deno.json",
+];
+
+/// Extensions of configuration files; those at the repository root count as manifests
+const CONFIG_EXTENSIONS: &[&str] = &["toml", "json", "yaml", "yml", "ini", "cfg"];
+
+/// Extensions of source code, as opposed to data, assets and other files
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "mjs", "cjs", "jsx", "ts", "tsx", "go", "java", "kt", "kts", "scala", "c", "h", "cc", "cpp",
+    "hpp", "cs", "fs", "swift", "m", "rb", "php", "pl", "lua", "dart", "ex", "exs", "erl", "hs", "ml", "clj", "r",
+    "jl", "zig", "nim", "vue", "svelte", "sh", "bash", "zsh", "ps1", "sql",
+];
+
+/// Directories holding documentation (at the repository root) and tests (anywhere)
+const DOC_DIRS: &[&str] = &["docs", "doc"];
+const TEST_DIRS: &[&str] = &["tests", "test", "__tests__", "spec", "specs", "testdata"];
+
+/// Tiers of `--order priority`, in output order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum PriorityTier {
+    Readme,   // README at the root
+    Docs,     // Other documents at the root, files under docs/
+    Manifest, // Build and dependency manifests anywhere, configuration at the root
+    Source,   // Source code
+    Tests,    // Files in test directories or named like tests
+    Other,    // Everything else
+}
+
+/// Sorts a file into its `--order priority` tier by its name and directories
+fn priority_tier(path: &str) -> PriorityTier {
+    let (dirs, name) = path.rsplit_once('/').unwrap_or(("", path));
+    let dirs: Vec<&str> = dirs.split('/').filter(|dir| !dir.is_empty()).collect();
+    let lower = name.to_lowercase();
+    let ext = lower.rsplit_once('.').map_or("", |(_, ext)| ext);
+    let at_root = dirs.is_empty();
+
+    if at_root && lower.starts_with("readme") {
+        return PriorityTier::Readme;
+    }
+    if (at_root && ext == "md") || dirs.first().is_some_and(|dir| DOC_DIRS.contains(dir)) {
+        return PriorityTier::Docs;
+    }
+    if MANIFEST_FILES.contains(&name)
+        || (lower.starts_with("requirements") && ext == "txt")
+        || (at_root && CONFIG_EXTENSIONS.contains(&ext))
+    {
+        return PriorityTier::Manifest;
+    }
+    let stem = lower.strip_suffix(&format!(".{}", ext)).unwrap_or(&lower);
+    if dirs.iter().any(|dir| TEST_DIRS.contains(dir))
+        || stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with(".test")
+        || stem.ends_with(".spec")
+    {
+        return PriorityTier::Tests;
+    }
+    if SOURCE_EXTENSIONS.contains(&ext) {
+        return PriorityTier::Source;
+    }
+    PriorityTier::Other
+}
+
+/// Collects the files (entries without children) of a structure, in structure order
+fn collect_files<'a>(entries: &'a mut [FileEntry], files: &mut Vec<&'a mut FileEntry>) {
+    for entry in entries {
+        match entry.children {
+            Some(ref mut children) => collect_files(children, files),
+            None => files.push(entry),
+        }
+    }
+}
+
+/// Computes the lowercase hex SHA-256 digest of the given bytes
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Path attributes relevant to concatenation, read from the repository's `.gitattributes`
+struct GitAttributes {
+    export_ignore: Gitignore, // Paths marked `export-ignore`
+    generated: Gitignore,     // Paths marked `linguist-generated`
+}
+
+impl GitAttributes {
+    /// Creates an attribute set that matches nothing
+    fn empty() -> Self {
+        Self {
+            export_ignore: Gitignore::empty(),
+            generated: Gitignore::empty(),
+        }
+    }
+
+    /// Parses `.gitattributes` at the repository root (missing file means no attributes)
+    fn load(repo_dir: &Path) -> Self {
+        let Ok(content) = fs::read_to_string(repo_dir.join(".gitattributes")) else {
+            return Self::empty();
+        };
+
+        // Unset attributes are added as negated patterns so that later lines win, as in git
+        let mut export_ignore = GitignoreBuilder::new(repo_dir);
+        let mut generated = GitignoreBuilder::new(repo_dir);
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else { continue };
+            for attribute in parts {
+                let (builder, set) = match attribute {
+                    "export-ignore" | "export-ignore=true" => (&mut export_ignore, true),
+                    "-export-ignore" | "!export-ignore" | "export-ignore=false" => (&mut export_ignore, false),
+                    "linguist-generated" | "linguist-generated=true" => (&mut generated, true),
+                    "-linguist-generated" | "!linguist-generated" | "linguist-generated=false" => (&mut generated, false),
+                    _ => continue,
+                };
+                let glob = if set { pattern.to_string() } else { format!("!{}", pattern) };
+                if let Err(e) = builder.add_line(None, &glob) {
+                    warn!("Ignoring invalid .gitattributes pattern '{}': {}", pattern, e);
+                }
+            }
+        }
+
+        Self {
+            export_ignore: export_ignore.build().unwrap_or_else(|_| Gitignore::empty()),
+            generated: generated.build().unwrap_or_else(|_| Gitignore::empty()),
+        }
+    }
+
+    /// Checks if a path (relative to the repository root) is marked `export-ignore`
+    fn is_export_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.export_ignore.matched(path, is_dir).is_ignore()
+    }
+
+    /// Checks if a path (relative to the repository root) is marked `linguist-generated`
+    fn is_generated(&self, path: &Path, is_dir: bool) -> bool {
+        self.generated.matched(path, is_dir).is_ignore()
+    }
+}
+
+/// Exclusion rules from one `.concatignore` file (gitignore syntax), scoped to its directory
+struct ConcatIgnore {
+    base: PathBuf,     // Directory of the rules, relative to the repository root
+    rules: Gitignore,  // Compiled patterns
+}
+
+impl ConcatIgnore {
+    /// Name of the per-directory ignore file
+    const FILE_NAME: &'static str = ".concatignore";
+
+    /// Name of the repository-wide ignore file, only read at the root
+    const ROOT_FILE_NAME: &'static str = ".gitconcatignore";
+
+    /// Compiles the given pattern files into rules for `dir` (None when no file exists)
+    fn load(dir: &Path, base: &Path, files: &[PathBuf]) -> Option<Self> {
+        let files: Vec<_> = files.iter().filter(|file| file.is_file()).collect();
+        if files.is_empty() {
+            return None;
+        }
+        let mut builder = GitignoreBuilder::new(dir);
+        for file in files {
+            if let Some(e) = builder.add(file) {
+                warn!("Problem reading {}: {}", file.display(), e);
+            }
+        }
+        match builder.build() {
+            Ok(rules) => Some(Self { base: base.to_path_buf(), rules }),
+            Err(e) => {
+                warn!("Ignoring invalid concatignore rules in {}: {}", dir.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Compiles patterns given on the command line or built in (None when there are none)
+    fn from_patterns<'a>(dir: &Path, patterns: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut empty = true;
+        for pattern in patterns {
+            empty = false;
+            if let Err(e) = builder.add_line(None, pattern) {
+                warn!("Ignoring invalid pattern '{}': {}", pattern, e);
+            }
+        }
+        if empty {
+            return None;
+        }
+        builder.build().ok().map(|rules| Self { base: PathBuf::new(), rules })
+    }
+
+    /// Matches a path (relative to the repository root) against a stack of rules, deepest first
+    fn matched(stack: &[&ConcatIgnore], path: &Path, is_dir: bool) -> Match<()> {
+        for ignore in stack.iter().rev() {
+            let Ok(relative) = path.strip_prefix(&ignore.base) else { continue };
+            match ignore.rules.matched_path_or_any_parents(relative, is_dir) {
+                Match::None => continue,
+                Match::Ignore(_) => return Match::Ignore(()),
+                Match::Whitelist(_) => return Match::Whitelist(()),
+            }
+        }
+        Match::None
+    }
+}
+
+/// Main processor struct that handles all file operations
+struct FileProcessor {
+    ignore_dirs: NameSet,               // Directories to ignore (e.g., .git, node_modules, *.egg-info)
+    ignore_hidden_dirs: NameSet,        // Dot-directories ignored by default (e.g., .idea, .vscode)
+    ignore_files: NameSet,              // Files to ignore (e.g., .DS_Store)
+    ignore_extensions: HashSet<String>, // File extensions to ignore (e.g., .exe, .dll)
+    languages: HashMap<String, String>, // File extension -> language used for code fences
+    language_patterns: globset::GlobSet, // File name wildcards from --lang-map
+    language_pattern_names: Vec<String>, // Language for each entry of language_patterns
+    format: OutputFormat,               // Output format to generate
+    hidden: HiddenPolicy,               // How dotfiles and dot-directories are handled
+    include_env: bool,                  // Include .env* files
+    max_depth: Option<usize>,           // Maximum directory depth to descend into
+    max_lines: Option<usize>,           // Omit the contents of files with more lines than this
+    limits: Vec<LimitRule>,             // Per-pattern limits, in order (the first match wins over max_lines)
+    preview_lines: Option<usize>,       // Keep only this many lines at the start and end of longer files
+    top: Option<usize>,                 // Print this many of the largest directories and files
+    max_output_size: Option<u64>,       // Maximum size of the generated document
+    on_limit: OnLimit,                  // What to do when the size limit is reached
+    normalize_eol: LineEndings,         // Line endings of text file contents
+    order: ContentOrder,                // Order of the file sections
+    wrap: Option<usize>,                // Hard-wrap content lines longer than this many characters
+    filter_cmd: Option<String>,         // Shell command each text file is piped through
+    path_prefix: Option<String>,        // Prepended to every emitted path (ends with '/')
+    flatten_paths: bool,                // Show only file names in markdown headings
+    colliding_names: HashSet<String>,   // File names shared by several files (with --flatten-paths)
+    anchors: HashMap<String, String>,   // File path -> HTML anchor id of its section
+    walk_threads: AtomicUsize,          // Threads still free for walking subdirectories
+    walk_errors: Mutex<Vec<(String, String)>>, // Entries the walk could not read: relative path, error
+    excluded_env: Mutex<Vec<String>>,   // .env* files left out by the dotfile policy (listed by --audit)
+    fail_fast: bool,                    // Abort on unreadable entries instead of reporting them (--strict)
+    incremental: bool,                  // Record file states for the manifest (--incremental)
+    manifest: bool,                     // Record file sections for the sidecar manifest (--manifest)
+    auditor: Option<Auditor>,           // Heuristics for risky files (--audit)
+    fail_on_audit: bool,                // Fail without writing output when the audit finds anything
+    previous: Option<Previous>,         // Output of the previous run, to reuse unchanged sections from
+    paths: Vec<String>,                 // Only include these files and directories (empty = everything)
+    branch: Option<String>,             // Branch to clone (from a `/tree/<branch>` URL)
+    lang: Option<HashSet<String>>,      // Only include files of these languages
+    exclude_lang: HashSet<String>,      // Exclude files of these languages
+    only_ext: Option<HashSet<String>>,  // Only include files with these extensions ("" = none)
+    embed_binary: Option<u64>,          // Embed binary files up to this size as base64
+    embed_images: bool,                 // Inline images up to MAX_EMBEDDED_IMAGE as data URIs
+    dedup: bool,                        // Collapse files with identical contents
+    hash: bool,                         // Add each file's SHA-256 to the structure
+    token: Option<String>,              // Access token for private HTTPS remotes
+    askpass: Option<Askpass>,           // Credential helper handing the token to git
+    ssh_key: Option<PathBuf>,           // Private key for SSH remotes
+    ssh_strict: Option<String>,         // StrictHostKeyChecking policy for SSH remotes
+    keep_empty_dirs: bool,              // Keep directories with no included children
+    front_matter: bool,                 // Prepend YAML front matter with document metadata
+    stats: bool,                        // Emit a per-language statistics table
+    toc: bool,                          // Emit a table of contents linking to the file sections
+    skip_generated: bool,               // Skip files marked linguist-generated
+    include_generated: bool,            // Keep the contents of files detected as generated or minified
+    attributes: GitAttributes,          // Attributes from the repository's .gitattributes
+    structure_fields: Option<Vec<StructureField>>, // Fields serialized in the structure (None = all)
+    structure_only: bool,      // Only emit the structure, without reading file contents
+    no_structure: bool,        // Leave out the structure section
+    interactive: bool,                  // Choose files from a terminal checklist
+    selection: Option<PathBuf>,         // Saved selection to apply
+    save_selection: Option<PathBuf>,    // Where to save the chosen selection
+    source: Option<RepoSource>,         // Forced local/remote handling (None = detect from the argument)
+    no_cache: bool,                     // Clone remotes into a temporary directory instead of the cache
+    via_api: bool,                      // Fetch github.com repositories through the API instead of cloning
+    refresh_cache: bool,                // Replace the cached clone with a fresh one
+    cache_dir: Option<PathBuf>,         // Clone cache location (None = default)
+    recurse_submodules: bool,           // Include the contents of git submodules
+    submodules: HashMap<String, String>, // Submodule path -> URL from .gitmodules
+    git_ref: Option<String>,            // Git ref of a local repository to read, through a temporary worktree
+    since: Option<String>,              // Only include files changed since this git ref
+    modified_since: Option<i64>,        // Only include the contents of files last modified after this time (Unix seconds)
+    show_mode: bool,                    // Include Unix file permissions in the structure
+    concatignore: Option<PathBuf>,      // Extra exclusion patterns file
+    patterns: Vec<String>,              // Patterns from --exclude and --include (as `!pattern`), in order
+    preset: Option<&'static Preset>,    // Built-in exclusion profile
+    show_ignores: bool,                 // Print the effective ignore rules instead of generating output
+    explicit_ignore: Option<ConcatIgnore>, // Compiled --exclude/--include patterns, matched before everything else
+}
+
+impl FileProcessor {
+    /// Creates a new FileProcessor with default ignore lists
+    fn new(config: &Config) -> Self {
+        // Initialize directories to ignore (names with `*`, `?` or `[` are glob patterns)
+        let ignore_dirs = NameSet::new([".git", "node_modules", "target", "dist", "build", "__pycache__", "*.egg-info"]);
+
+        // Initialize hidden directories to ignore by default (editor and tool state)
+        let ignore_hidden_dirs = NameSet::new([".idea", ".vscode", ".venv", ".cache"]);
+
+        // Initialize files to ignore
+        let ignore_files = NameSet::new([
+            ".DS_Store",
+            ".git", // gitlink file inside submodules and worktrees
+            ConcatIgnore::FILE_NAME,
+            ConcatIgnore::ROOT_FILE_NAME,
+            "yarn.lock",
+        ]);
+
+        // Initialize file extensions to ignore
+        let mut ignore_extensions = HashSet::new();
+        for ext in [
+            // Binaries
+            "exe", "dll", "so", "dylib",
+            // Images
+            "png", "jpg", "jpeg", "gif", "ico", "bmp", "tiff", "webp",
+            // Archives
+            "zip", "rar", "7z", "tar", "gz", "bz2",
+            // Other binary formats
+            "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx",
+            "class", "pyc", "pyo", "pyd",
+            // Audio/Video
+            "mp3", "mp4", "wav", "avi", "mov", "flv", "mkv",
+            // Database
+            "db", "sqlite", "sqlite3",
+        ] {
+            ignore_extensions.insert(ext.to_string());
+        }
+
+        // Map file extensions to their corresponding language for syntax highlighting
+        let mut languages = HashMap::new();
+        for (extensions, language) in [
+            (&["js", "jsx"][..], "javascript"),
+            (&["ts", "tsx"], "typescript"),
+            (&["py"], "python"),
+            (&["rb"], "ruby"),
+            (&["java"], "java"),
+            (&["cs"], "csharp"),
+            (&["cpp", "hpp"], "cpp"),
+            (&["c", "h"], "c"),
+            (&["rs"], "rust"),
+            (&["go"], "go"),
+            (&["php"], "php"),
+            (&["html"], "html"),
+            (&["css"], "css"),
+            (&["scss"], "scss"),
+            (&["md"], "markdown"),
+            (&["json"], "json"),
+            (&["xml"], "xml"),
+            (&["yaml", "yml"], "yaml"),
+            (&["sh", "bash"], "bash"),
+            (&["sql"], "sql"),
+            (&["kt"], "kotlin"),
+            (&["swift"], "swift"),
+            (&["r"], "r"),
+            (&["lua"], "lua"),
+            (&["pl", "perl"], "perl"),
+            (&["dart"], "dart"),
+            (&["ex", "exs"], "elixir"),
+            (&["erl"], "erlang"),
+            (&["fs", "fsx"], "fsharp"),
+            (&["hs"], "haskell"),
+            (&["scala"], "scala"),
+            (&["toml"], "toml"),
+        ] {
+            for ext in extensions {
+                languages.insert(ext.to_string(), language.to_string());
+            }
+        }
+
+        // Apply --lang-map overrides: plain extensions replace table entries, wildcards match file names
+        let mut patterns = globset::GlobSetBuilder::new();
+        let mut language_pattern_names = Vec::new();
+        for (key, language) in &config.lang_map {
+            if is_glob(key) {
+                if let Ok(glob) = globset::Glob::new(key) {
+                    patterns.add(glob);
+                    language_pattern_names.push(language.clone());
+                }
+            } else {
+                languages.insert(key.trim_start_matches('.').to_lowercase(), language.clone());
+            }
+        }
+        let language_patterns = patterns.build().unwrap_or_else(|_| globset::GlobSet::empty());
+
+        Self {
+            ignore_dirs,
+            ignore_hidden_dirs,
+            ignore_files,
+            ignore_extensions,
+            languages,
+            language_patterns,
+            language_pattern_names,
+            format: config.format,
+            hidden: config.hidden,
+            include_env: config.include_env,
+            max_depth: config.max_depth,
+            max_lines: config.max_lines,
+            limits: config.limits.clone(),
+            preview_lines: config.preview_lines,
+            top: config.top,
+            max_output_size: config.max_output_size,
+            on_limit: config.on_limit,
+            normalize_eol: config.normalize_eol,
+            order: config.order,
+            wrap: config.wrap,
+            filter_cmd: config.filter_cmd.clone(),
+            path_prefix: config.path_prefix.clone(),
+            flatten_paths: config.flatten_paths,
+            colliding_names: HashSet::new(),
+            anchors: HashMap::new(),
+            walk_threads: AtomicUsize::new(thread::available_parallelism().map_or(1, |n| n.get()).saturating_sub(1)),
+            walk_errors: Mutex::new(Vec::new()),
+            excluded_env: Mutex::new(Vec::new()),
+            fail_fast: config.strict.as_ref().is_some_and(|reasons| reasons.contains(&Reason::Unreadable)),
+            incremental: config.incremental,
+            manifest: config.manifest.is_some(),
+            auditor: config.audit.then(Auditor::new),
+            fail_on_audit: config.fail_on_audit,
+            previous: None,
+            paths: config.paths.clone(),
+            branch: config.branch.clone(),
+            lang: config.lang.as_ref().map(|names| names.iter().cloned().collect()),
+            exclude_lang: config.exclude_lang.iter().cloned().collect(),
+            only_ext: config.only_ext.clone(),
+            embed_binary: config.embed_binary,
+            embed_images: config.embed_images,
+            dedup: config.dedup,
+            hash: config.hash,
+            token: config.token.clone(),
+            askpass: None,
+            ssh_key: config.ssh_key.clone(),
+            ssh_strict: config.ssh_strict.clone(),
+            keep_empty_dirs: config.keep_empty_dirs,
+            front_matter: config.front_matter,
+            stats: config.stats,
+            toc: config.toc,
+            skip_generated: config.skip_generated,
+            include_generated: config.include_generated,
+            attributes: GitAttributes::empty(),
+            structure_fields: config.structure_fields.clone(),
+            structure_only: config.structure_only,
+            no_structure: config.no_structure,
+            interactive: config.interactive,
+            selection: config.selection.clone(),
+            save_selection: config.save_selection.clone(),
+            source: config.source,
+            no_cache: config.no_cache,
+            via_api: config.via_api,
+            refresh_cache: config.refresh_cache,
+            cache_dir: config.cache_dir.clone(),
+            recurse_submodules: config.recurse_submodules,
+            submodules: HashMap::new(),
+            git_ref: config.git_ref.clone(),
+            since: config.since.clone(),
+            modified_since: config.modified_since,
+            show_mode: config.show_mode,
+            concatignore: config.concatignore.clone(),
+            patterns: config.patterns.clone(),
+            preset: config.preset,
+            show_ignores: config.show_ignores,
+            explicit_ignore: None,
+        }
+    }
+
+    /// Recursively builds the file structure starting from the given directory
+    fn get_file_structure(
+        &self,
+        dir: &Path,
+        base_path: &Path,
+        ignores: &[&ConcatIgnore],
+    ) -> Result<Vec<FileEntry>, ConcatError> {
+        let entries = fs::read_dir(dir).map_err(|e| ConcatError::io(dir, e))?;
+
+        // Nested .concatignore files apply to their own directory (the root one is loaded up front)
+        let nested = if base_path.as_os_str().is_empty() {
+            None
+        } else {
+            ConcatIgnore::load(dir, base_path, &[dir.join(ConcatIgnore::FILE_NAME)])
+        };
+        let mut ignores = ignores.to_vec();
+        ignores.extend(nested.as_ref());
+        // Ignored directories are only entered when a negation pattern could re-include something
+        let has_negations = ignores.iter().copied().chain(self.explicit_ignore.iter()).any(|ignore| ignore.rules.num_whitelists() > 0);
+
+        // Subdirectories are walked on other threads while some are free. Results are collected in name
+        // order, so the structure and the first error returned don't depend on which thread finishes first.
+        let mut entries = entries.collect::<Result<Vec<_>, _>>().map_err(|e| ConcatError::io(dir, e))?;
+        entries.sort_by_key(|entry| entry.file_name());
+        let results: Vec<_> = thread::scope(|scope| {
+            let pending: Vec<_> = entries
+                .into_iter()
+                .map(|entry| {
+                    let is_dir = entry.path().is_dir();
+                    if is_dir && self.acquire_walk_thread() {
+                        let ignores = &ignores;
+                        Walk::Spawned(scope.spawn(move || {
+                            let result = self.structure_entry(entry, is_dir, base_path, ignores, has_negations);
+                            self.walk_threads.fetch_add(1, Ordering::Release);
+                            result
+                        }))
+                    } else {
+                        Walk::Done(self.structure_entry(entry, is_dir, base_path, &ignores, has_negations))
+                    }
+                })
+                .collect();
+            pending
+                .into_iter()
+                .map(|walk| match walk {
+                    Walk::Spawned(handle) => handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+                    Walk::Done(result) => result,
+                })
+                .collect()
+        });
+        let mut structure = Vec::new();
+        for result in results {
+            structure.extend(result?);
+        }
+        structure.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // Names that differ only in case stay apart by their exact spelling (and get distinct anchors),
+        // but can't be checked out side by side on case-insensitive file systems (macOS, Windows)
+        let mut by_lowercase: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for entry in &structure {
+            by_lowercase.entry(entry.name.to_lowercase()).or_default().push(&entry.path);
+        }
+        for paths in by_lowercase.values().filter(|paths| paths.len() > 1) {
+            warn!("{} differ only in case and collide on case-insensitive file systems", paths.join(" and "));
+        }
+
+        Ok(structure)
+    }
+
+    /// Records an entry the walk could not read and returns the error message for its marker
+    fn walk_error(&self, relative_path: &Path, error: io::Error) -> String {
+        let path = relative_path.to_string_lossy().into_owned();
+        warn!("Skipping unreadable {}: {}", path, error);
+        let error = error.to_string();
+        self.walk_errors.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push((path, error.clone()));
+        error
+    }
+
+    /// Builds the structure entry for one directory entry (None when it is excluded)
+    fn structure_entry(
+        &self,
+        entry: fs::DirEntry,
+        is_dir: bool,
+        base_path: &Path,
+        ignores: &[&ConcatIgnore],
+        has_negations: bool,
+    ) -> Result<Option<FileEntry>, ConcatError> {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative_path = base_path.join(&name);
+
+        // Skip paths excluded through .gitattributes
+        if self.attributes.is_export_ignored(&relative_path, is_dir)
+            || (self.skip_generated && self.attributes.is_generated(&relative_path, is_dir))
+        {
+            return Ok(None);
+        }
+
+        // Skip paths excluded through --exclude, .concatignore or the preset
+        let matched = match self.explicit_ignore.as_ref().map(|explicit| ConcatIgnore::matched(&[explicit], &relative_path, is_dir)) {
+            Some(matched) if !matched.is_none() => matched,
+            _ => ConcatIgnore::matched(ignores, &relative_path, is_dir),
+        };
+        if matched.is_ignore() && !(is_dir && has_negations) {
+            return Ok(None);
+        }
+
+        // Skip paths outside the requested path filters
+        let relative_key = relative_path.to_string_lossy().replace('\\', "/");
+        if !self.is_path_selected(&relative_key, is_dir) {
+            return Ok(None);
+        }
+
+        // Submodules whose contents are not included get a placeholder instead of vanishing as empty directories
+        if let Some(url) = self.submodules.get(&relative_key) {
+            if !self.recurse_submodules || is_empty_dir(&path) {
+                return Ok(Some(FileEntry {
+                    entry_type: "submodule".to_string(),
+                    name,
+                    path: relative_key,
+                    url: Some(url.clone()),
+                    children: Some(Vec::new()),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        if is_dir {
+            // Process directory if it's not in ignore list
+            if self.should_ignore_dir(&name) {
+                return Ok(None);
+            }
+            let children = match self.get_file_structure(&path, &relative_path, ignores) {
+                Ok(children) => children,
+                // An unreadable directory stays in the tree, marked, and the walk goes on
+                Err(ConcatError::Io { source, .. }) if !self.fail_fast => {
+                    let error = self.walk_error(&relative_path, source);
+                    return Ok(Some(FileEntry {
+                        entry_type: "directory".to_string(),
+                        name,
+                        path: relative_path.to_string_lossy().into_owned(),
+                        error: Some(error),
+                        children: Some(Vec::new()),
+                        ..Default::default()
+                    }));
+                }
+                Err(e) => return Err(e),
+            };
+
+            // Directories at the depth limit only keep aggregate totals of their contents
+            let depth = self.walk_depth(&relative_key);
+            if self.max_depth.is_some_and(|max| depth.is_some_and(|depth| depth >= max)) && !children.is_empty() {
+                let (file_count, size) = count_files(&children);
+                return Ok(Some(FileEntry {
+                    entry_type: "directory".to_string(),
+                    name,
+                    path: relative_path.to_string_lossy().into_owned(),
+                    size: Some(size),
+                    file_count: Some(file_count),
+                    truncated: Some(true),
+                    children: Some(Vec::new()),
+                    ..Default::default()
+                }));
+            }
+
+            // Directories without any included files are dropped unless requested
+            Ok((!children.is_empty() || self.keep_empty_dirs).then(|| FileEntry {
+                entry_type: "directory".to_string(),
+                name,
+                path: relative_path.to_string_lossy().into_owned(),
+                children: Some(children),
+                ..Default::default()
+            }))
+        } else {
+            // Process file if it's not in ignore list (small binaries are kept when embedding)
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                // e.g. a build artifact deleted while walking
+                Err(e) if !self.fail_fast => {
+                    if self.should_ignore_file(&name) {
+                        return Ok(None);
+                    }
+                    let error = self.walk_error(&relative_path, e);
+                    return Ok(Some(FileEntry {
+                        entry_type: "file".to_string(),
+                        name,
+                        path: relative_path.to_string_lossy().into_owned(),
+                        error: Some(error),
+                        ..Default::default()
+                    }));
+                }
+                Err(e) => return Err(ConcatError::io(&path, e)),
+            };
+            let size = metadata.len();
+            let included = (!self.should_ignore_file(&name)
+                || self.should_embed_binary(&name, size)
+                || self.should_embed_image(&name, size))
+                && self.is_language_selected(&relative_path);
+            // The audit can't scan environment files that are left out, but still names them
+            if !included && self.auditor.is_some() && !self.include_env && name.starts_with(".env") {
+                self.excluded_env.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(relative_key);
+            }
+            Ok(included.then(|| FileEntry {
+                entry_type: "file".to_string(),
+                name,
+                path: relative_path.to_string_lossy().into_owned(),
+                size: Some(size),
+                mode: if self.show_mode { file_mode(&metadata) } else { None },
+                ..Default::default()
+            }))
+        }
+    }
+
+    /// Takes one of the threads available to the structure walk, if any is free
+    fn acquire_walk_thread(&self) -> bool {
+        self.walk_threads.fetch_update(Ordering::AcqRel, Ordering::Acquire, |free| free.checked_sub(1)).is_ok()
+    }
+
+    /// Checks a path against the positional path filters; directories leading to a filter are entered
+    fn is_path_selected(&self, path: &str, is_dir: bool) -> bool {
+        self.paths.is_empty()
+            || self.paths.iter().any(|filter| is_within(filter, path) || (is_dir && is_within(path, filter)))
+    }
+
+    /// Depth of a directory for --max-depth: counted from the repository root, or from the --path it
+    /// lies in. Directories leading to a --path have no depth, so they are never truncated.
+    fn walk_depth(&self, path: &str) -> Option<usize> {
+        let depth = |path: &str| path.split('/').filter(|part| !part.is_empty()).count();
+        if self.paths.is_empty() {
+            return Some(depth(path));
+        }
+        self.paths.iter().filter(|filter| is_within(filter, path)).map(|filter| depth(path) - depth(filter)).min()
+    }
+
+    /// Checks if a file should be ignored based on its name or extension
+    fn should_ignore_file(&self, filename: &str) -> bool {
+        // With --only-ext the allowed extensions replace the binary extension list
+        if let Some(only_ext) = &self.only_ext {
+            let ext = Path::new(filename).extension().map(|ext| ext.to_string_lossy().to_lowercase());
+            return self.should_ignore_file_name(filename) || !only_ext.contains(ext.as_deref().unwrap_or(""));
+        }
+        self.should_ignore_file_name(filename) || self.has_binary_extension(filename)
+    }
+
+    /// Checks the name-based file rules: the ignore list and the dotfile policy
+    fn should_ignore_file_name(&self, filename: &str) -> bool {
+        // Check if the file is in the ignore list
+        if self.ignore_files.contains(filename) {
+            return true;
+        }
+
+        // Environment files usually hold secrets
+        if filename.starts_with(".env") && !self.include_env {
+            return true;
+        }
+
+        filename.starts_with('.') && self.hidden == HiddenPolicy::Exclude
+    }
+
+    /// Checks if a directory should be skipped based on its name and the dotfile policy
+    fn should_ignore_dir(&self, name: &str) -> bool {
+        // Check if the directory is in the ignore list (this always covers .git)
+        if self.ignore_dirs.contains(name) {
+            return true;
+        }
+
+        if !name.starts_with('.') {
+            return false;
+        }
+        match self.hidden {
+            HiddenPolicy::Default => self.ignore_hidden_dirs.contains(name),
+            HiddenPolicy::Include => false,
+            HiddenPolicy::Exclude => true,
+        }
+    }
+
+    /// Checks if a file has a binary extension from the ignore list
+    fn has_binary_extension(&self, filename: &str) -> bool {
+        Path::new(filename)
+            .extension()
+            .is_some_and(|ext| self.ignore_extensions.contains(&ext.to_string_lossy().to_lowercase()))
+    }
+
+    /// Checks if a binary file is small enough to be embedded as base64
+    fn should_embed_binary(&self, filename: &str, size: u64) -> bool {
+        self.only_ext.is_none()
+            && !self.should_ignore_file_name(filename)
+            && self.has_binary_extension(filename)
+            && self.embed_binary.is_some_and(|max| size <= max)
+    }
+
+    /// Checks if an image is small enough to be inlined (--embed-images), even with an ignored extension
+    fn should_embed_image(&self, filename: &str, size: u64) -> bool {
+        self.embed_images
+            && self.only_ext.is_none()
+            && !self.should_ignore_file_name(filename)
+            && image_mime_type(filename).is_some()
+            && size <= MAX_EMBEDDED_IMAGE
+    }
+
+    /// Reads a file and decides how its content should be emitted
+    fn read_content(&self, full_path: &Path, entry: &mut FileEntry, state: &mut ProcessState) -> Result<FileContent, ConcatError> {
+        let bytes = match fs::read(full_path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                // One unreadable file doesn't spoil the rest of the run; it is reported at the end
+                let reason = if full_path.is_symlink() && !full_path.exists() { Reason::BrokenSymlink } else { Reason::Unreadable };
+                debug!("Unable to read {}: {}", entry.path, e);
+                state.report.add(&entry.path, reason, Some(e.to_string()));
+                return Ok(FileContent::Unreadable(e.to_string()));
+            }
+        };
+        // The manifest records the hash even without --hash; process_files takes it back out
+        if self.hash || self.incremental || self.manifest {
+            entry.hash = Some(sha256_hex(&bytes));
+        }
+
+        // Small images are shown inline instead of being skipped as binary (SVG included)
+        if self.should_embed_image(&entry.name, bytes.len() as u64) {
+            if let Some(mime_type) = image_mime_type(&entry.name) {
+                return Ok(FileContent::Image(mime_type, bytes));
+            }
+        }
+
+        // UTF-16 files are recognized by their BOM before the binary check (they contain NUL bytes)
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(&bytes) {
+            if encoding != encoding_rs::UTF_8 {
+                let (text, _, _) = encoding.decode(&bytes);
+                entry.encoding = Some(encoding.name().to_string());
+                return Ok(FileContent::Text(text.into_owned()));
+            }
+        }
+
+        // Binary files are embedded when small enough, otherwise skipped
+        if self.has_binary_extension(&entry.name) || looks_binary(&bytes) {
+            if self.embed_binary.is_some_and(|max| bytes.len() as u64 <= max) {
+                return Ok(FileContent::Binary(bytes));
+            }
+            state.report.add(&entry.path, Reason::Binary, Some(format!("{} bytes", bytes.len())));
+            debug!("Skipping binary content of {}", entry.path);
+            return Ok(FileContent::Skipped("Binary file".to_string()));
+        }
+
+        // Generated and minified files are mostly noise; their size is kept as a placeholder
+        // Converted formats (notebooks) are never minified, however long their embedded outputs are
+        let classification = classify_generated(&entry.name, &bytes).filter(|_| convert::for_file(&entry.name).is_none());
+        if let Some(classification) = classification {
+            entry.classification = Some(classification.to_string());
+            state.generated += 1;
+            if !self.include_generated {
+                state.report.add(&entry.path, Reason::Generated, Some(format!("{}, {} bytes", classification, bytes.len())));
+                debug!("Skipping {} content of {}", classification, entry.path);
+                return Ok(FileContent::Generated(bytes.len()));
+            }
+        }
+
+        let bytes = match String::from_utf8(bytes) {
+            Ok(content) => return Ok(FileContent::Text(content)),
+            Err(e) => e.into_bytes(),
+        };
+
+        // Legacy encodings (e.g. Shift_JIS, windows-1252) are detected and transcoded. Text where valid multi-byte
+        // UTF-8 outweighs the invalid sequences is UTF-8 with a few damaged bytes, not windows-1252 mojibake.
+        let (valid, invalid) = bytes.utf8_chunks().fold((0, 0), |(valid, invalid), chunk| {
+            let non_ascii = chunk.valid().chars().filter(|c| !c.is_ascii()).count();
+            (valid + non_ascii, invalid + usize::from(!chunk.invalid().is_empty()))
+        });
+        let mut detector = chardetng::EncodingDetector::new(chardetng::Iso2022JpDetection::Allow);
+        detector.feed(&bytes, true);
+        let encoding = detector.guess(None, chardetng::Utf8Detection::Allow);
+        if encoding != encoding_rs::UTF_8 && valid < invalid {
+            let (text, had_errors) = encoding.decode_without_bom_handling(&bytes);
+            if !had_errors {
+                entry.encoding = Some(encoding.name().to_string());
+                return Ok(FileContent::Text(text.into_owned()));
+            }
+        }
+
+        // Text with invalid UTF-8 sequences keeps its readable parts
+        state.report.add(&entry.path, Reason::NonUtf8, Some(format!("{} replacement character(s)", invalid)));
+        debug!("{} is not valid UTF-8, inserted {} replacement character(s)", entry.path, invalid);
+        Ok(FileContent::Text(String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// Replaces content already seen in an earlier file with a reference to that file
+    fn deduplicate(&self, content: FileContent, entry: &FileEntry, state: &mut ProcessState) -> FileContent {
+        let bytes = match &content {
+            FileContent::Text(text) if !text.is_empty() => text.as_bytes(),
+            FileContent::Binary(bytes) if !bytes.is_empty() => bytes.as_slice(),
+            _ => return content,
+        };
+
+        // With --hash the on-disk hash is already known; every file then uses it, so keys stay comparable
+        let hash = entry.hash.clone().unwrap_or_else(|| sha256_hex(bytes));
+        if let Some(first_path) = state.seen_hashes.get(&hash) {
+            state.duplicates += 1;
+            state.bytes_saved += bytes.len() as u64;
+            FileContent::Duplicate(first_path.clone())
+        } else {
+            state.seen_hashes.insert(hash, entry.path.clone());
+            content
+        }
+    }
+
+    /// Determines the programming language based on file name patterns and extension
+    fn get_language_from_ext(&self, filepath: &Path) -> String {
+        // User-supplied wildcard patterns take precedence (first match wins)
+        if let Some(name) = filepath.file_name() {
+            if let Some(&index) = self.language_patterns.matches(name).first() {
+                return self.language_pattern_names[index].clone();
+            }
+        }
+
+        let extension = filepath
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        self.languages.get(&extension).cloned().unwrap_or_default()
+    }
+
+    /// Language name used for filtering and statistics (`other` when unknown)
+    fn language_bucket(&self, filepath: &Path) -> String {
+        let lang = self.get_language_from_ext(filepath);
+        if lang.is_empty() {
+            "other".to_string()
+        } else {
+            lang
+        }
+    }
+
+    /// Checks a file against the --lang and --exclude-lang filters
+    fn is_language_selected(&self, filepath: &Path) -> bool {
+        if self.lang.is_none() && self.exclude_lang.is_empty() {
+            return true;
+        }
+        let bucket = self.language_bucket(filepath);
+        self.lang.as_ref().is_none_or(|lang| lang.contains(&bucket)) && !self.exclude_lang.contains(&bucket)
+    }
+
+    /// Rejects --lang/--exclude-lang names that no file could ever match
+    fn validate_language_filters(&self) -> Result<(), ConcatError> {
+        let mut known: BTreeSet<&str> = self.languages.values().map(String::as_str).collect();
+        known.extend(self.language_pattern_names.iter().map(String::as_str));
+        known.insert("other");
+        for (option, name) in self
+            .lang
+            .iter()
+            .flatten()
+            .map(|name| ("--lang", name))
+            .chain(self.exclude_lang.iter().map(|name| ("--exclude-lang", name)))
+        {
+            if !known.contains(name.as_str()) {
+                return Err(ConcatError::InvalidArguments(format!(
+                    "Unknown language '{}' in {} (known: {})",
+                    name,
+                    option,
+                    known.into_iter().collect::<Vec<_>>().join(", ")
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds a git command with the SSH and credential settings for the given remote
+    fn git_command(&self, repo_path: &str) -> Command {
+        let mut git_cmd = Command::new("git");
+
+        // Add SSH specific flags if using SSH
+        if repo_path.starts_with("git@") || repo_path.starts_with("ssh://") {
+            if let Some(ssh_command) = self.ssh_command() {
+                git_cmd.env("GIT_SSH_COMMAND", ssh_command);
+            }
+        }
+
+        // HTTPS remotes get the access token through the askpass helper
+        if let (Some(askpass), Some(token)) = (&self.askpass, &self.token) {
+            askpass.apply(&mut git_cmd, token);
+        }
+        git_cmd
+    }
+
+    /// Builds the SSH command for git from --ssh-key/--ssh-strict, on top of any GIT_SSH_COMMAND.
+    /// Returns None when an existing GIT_SSH_COMMAND should be used unchanged.
+    fn ssh_command(&self) -> Option<String> {
+        let configured = std::env::var("GIT_SSH_COMMAND").ok().filter(|command| !command.trim().is_empty());
+        if configured.is_some() && self.ssh_key.is_none() && self.ssh_strict.is_none() {
+            return None;
+        }
+
+        // Without an explicit policy, new hosts are accepted unless the user brought their own command
+        let strict = match (&self.ssh_strict, &configured) {
+            (Some(strict), _) => Some(strict.as_str()),
+            (None, Some(_)) => None,
+            (None, None) => Some("accept-new"),
+        };
+        let mut command = configured.unwrap_or_else(|| "ssh".to_string());
+        if let Some(key) = &self.ssh_key {
+            command.push_str(&format!(" -i {} -o IdentitiesOnly=yes", shell_quote(&key.to_string_lossy())));
+        }
+        if let Some(strict) = strict {
+            command.push_str(&format!(" -o StrictHostKeyChecking={}", strict));
+        }
+        Some(command)
+    }
+
+    /// Clones a remote repository into the target directory
+    /// With path filters, only the blobs below those paths are downloaded (partial, sparse clone).
+    fn clone_repository(&self, repo_path: &str, target: &Path) -> Result<(), ConcatError> {
+        let partial = !self.paths.is_empty();
+        match self.run_clone(repo_path, target, partial) {
+            Err(e) if partial => {
+                // Not every server supports partial clone; fall back to downloading everything
+                warn!("Partial clone failed, retrying with a full clone: {}", e);
+                let _ = fs::remove_dir_all(target);
+                self.run_clone(repo_path, target, false)?;
+            }
+            result => result?,
+        }
+        if partial {
+            self.update_sparse_checkout(target, repo_path)?;
+            report_partial_clone(target);
+        }
+        Ok(())
+    }
+
+    /// Runs `git clone`, optionally as a blobless sparse clone
+    fn run_clone(&self, repo_path: &str, target: &Path, partial: bool) -> Result<(), ConcatError> {
+        // Build git command with appropriate flags
+        let mut git_cmd = self.git_command(repo_path);
+        git_cmd.arg("clone");
+        if self.recurse_submodules {
+            git_cmd.args(["--recurse-submodules", "--shallow-submodules"]);
+        }
+        if partial {
+            git_cmd.args(["--filter=blob:none", "--sparse"]);
+        }
+        if let Some(branch) = &self.branch {
+            git_cmd.args(["--branch", branch]);
+        }
+        // `--` keeps a URL starting with `-` from being read as an option; the target is a separate
+        // argument, which Command quotes on Windows, so spaces in it are safe
+        git_cmd.arg("--").arg(repo_path).arg(target);
+
+        // Execute the command
+        let output = git_cmd.output().map_err(|e| ConcatError::CloneFailed {
+            url: redact_url(repo_path),
+            stderr: format!("unable to run git: {}", e),
+        })?;
+
+        // Collect any error messages from git, never revealing the token
+        let mut stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if let Some(token) = &self.token {
+            stderr = stderr.replace(token.as_str(), "***");
+        }
+        if !output.status.success() {
+            return Err(ConcatError::CloneFailed { url: redact_url(repo_path), stderr });
+        }
+        if !stderr.is_empty() {
+            info!("Git output: {}", stderr);
+        }
+        Ok(())
+    }
+
+    /// Restricts the checkout to the path filters, or restores the full checkout of an earlier sparse clone
+    fn update_sparse_checkout(&self, repo_dir: &Path, repo_path: &str) -> Result<(), ConcatError> {
+        if self.paths.is_empty() {
+            if git_output(repo_dir, &["config", "--bool", "core.sparseCheckout"]).as_deref() == Some("true") {
+                self.run_git(repo_dir, &["sparse-checkout", "disable"], repo_path)?;
+            }
+            return Ok(());
+        }
+
+        // Anchored gitignore-style patterns match both files and whole directories
+        let patterns: Vec<String> = self.paths.iter().map(|path| format!("/{}", path)).collect();
+        let mut args = vec!["sparse-checkout", "set", "--no-cone"];
+        args.extend(patterns.iter().map(String::as_str));
+        self.run_git(repo_dir, &args, repo_path)
+    }
+
+    /// Runs a git command against a clone, reporting failures as clone errors (with the token masked)
+    fn run_git(&self, repo_dir: &Path, args: &[&str], repo_path: &str) -> Result<(), ConcatError> {
+        let mut git_cmd = self.git_command(repo_path);
+        git_cmd.arg("-C").arg(repo_dir);
+        let output = git_cmd.args(args).output().map_err(|e| ConcatError::CloneFailed {
+            url: redact_url(repo_path),
+            stderr: format!("unable to run git: {}", e),
+        })?;
+        if !output.status.success() {
+            let mut stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            if let Some(token) = &self.token {
+                stderr = stderr.replace(token.as_str(), "***");
+            }
+            return Err(ConcatError::CloneFailed { url: redact_url(repo_path), stderr });
+        }
+        Ok(())
+    }
+
+    /// Fetches a github.com repository through the API into a temporary directory (--via-api).
+    /// Returns None, after a warning, when the repository has to be cloned instead.
+    fn fetch_via_api(&self, repo_path: &str) -> Result<Option<TempDir>, ConcatError> {
+        let Some(repo) = github::Repo::parse(repo_path) else {
+            warn!("--via-api only supports github.com repositories, cloning {} instead", repo_path);
+            return Ok(None);
+        };
+        let dir = TempDir::new().map_err(|e| ConcatError::io(&std::env::temp_dir(), e))?;
+        match repo.fetch(self.branch.as_deref(), &self.paths, self.token.as_deref(), dir.path()) {
+            Ok(()) => Ok(Some(dir)),
+            Err(e) => {
+                warn!("GitHub API request failed ({}), cloning instead", e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns an up-to-date clone of a remote repository from the clone cache
+    /// The returned lock file keeps other runs away from the clone until it is dropped.
+    fn cached_clone(&self, repo_path: &str) -> Result<(PathBuf, fs::File), ConcatError> {
+        let cache_root = self.cache_dir.clone().unwrap_or_else(default_cache_dir);
+        let key = cache_key(repo_path);
+        let repo_dir = cache_root.join(&key);
+
+        // Serialize concurrent runs against the same URL
+        fs::create_dir_all(&cache_root).map_err(|e| ConcatError::io(&cache_root, e))?;
+        let lock_path = cache_root.join(format!("{}.lock", key));
+        let lock = fs::File::create(&lock_path).map_err(|e| ConcatError::io(&lock_path, e))?;
+        if lock.try_lock().is_err() {
+            info!("Waiting for another run using {:?}...", repo_dir);
+            lock.lock().map_err(|e| ConcatError::io(&lock_path, e))?;
+        }
+
+        // A broken or explicitly refreshed entry is replaced by a fresh clone
+        if repo_dir.exists() && (self.refresh_cache || !repo_dir.join(".git").is_dir()) {
+            fs::remove_dir_all(&repo_dir).map_err(|e| ConcatError::io(&repo_dir, e))?;
+        }
+
+        if repo_dir.exists() {
+            info!("Updating cached clone in {:?}...", repo_dir);
+            match self.run_git(&repo_dir, &["fetch", "--prune", "origin"], repo_path) {
+                Ok(()) => {
+                    match &self.branch {
+                        Some(branch) => self.run_git(
+                            &repo_dir,
+                            &["checkout", "--force", "-B", branch, &format!("origin/{}", branch)],
+                            repo_path,
+                        )?,
+                        None => self.run_git(&repo_dir, &["reset", "--hard", "origin/HEAD"], repo_path)?,
+                    }
+                    self.run_git(&repo_dir, &["clean", "-ffdx"], repo_path)?;
+                    self.update_sparse_checkout(&repo_dir, repo_path)?;
+                    if self.recurse_submodules {
+                        self.run_git(
+                            &repo_dir,
+                            &["submodule", "update", "--init", "--recursive", "--depth", "1"],
+                            repo_path,
+                        )?;
+                    }
+                }
+                // Offline runs still work from the last fetched state
+                Err(e) => warn!("Using cached clone without updating it: {}", e),
+            }
+        } else {
+            info!("Cloning repository to {:?}...", repo_dir);
+            if let Err(e) = self.clone_repository(repo_path, &repo_dir) {
+                let _ = fs::remove_dir_all(&repo_dir);
+                return Err(e);
+            }
+        }
+        Ok((repo_dir, lock))
+    }
+
+    /// Generates the complete markdown document for the repository
+    /// JSON Lines records are written to `stream` as they are produced, and an empty document is returned.
+    fn generate_markdown(&mut self, repo_path: &str, stream: Option<&mut OutputWriter>) -> Result<Document, ConcatError> {
+        // Handle both local paths and remote repositories
+        let temp_dir;
+        let _cache_lock;
+        let worktree;
+        let is_remote = match self.source {
+            Some(source) => source == RepoSource::Remote,
+            None => location::is_remote(repo_path),
+        };
+        let api_dir = if is_remote && self.via_api { self.fetch_via_api(repo_path)? } else { None };
+        let repo_dir = if let Some(api_dir) = &api_dir {
+            api_dir.path().to_path_buf()
+        } else if is_remote {
+            if self.token.is_some() && self.askpass.is_none() {
+                self.askpass = Some(Askpass::create()?);
+            }
+            if self.no_cache {
+                // Clone remote repository to temporary directory
+                temp_dir = TempDir::new().map_err(|e| ConcatError::io(&std::env::temp_dir(), e))?;
+                info!("Cloning repository to {:?}...", temp_dir.path());
+                self.clone_repository(repo_path, temp_dir.path())?;
+                temp_dir.path().to_path_buf()
+            } else {
+                let (repo_dir, lock) = self.cached_clone(repo_path)?;
+                _cache_lock = lock;
+                repo_dir
+            }
+        } else {
+            // Fail early with a clear message instead of deep inside the traversal
+            let mut path = PathBuf::from(repo_path);
+            if !path.is_dir() {
+                return Err(ConcatError::RepoNotFound(repo_path.to_string()));
+            }
+
+            // Another ref is read from a temporary worktree instead of checking it out in place
+            if let Some(git_ref) = &self.git_ref {
+                worktree = Worktree::add(&path, git_ref)?;
+                path = worktree.path().to_path_buf();
+            }
+
+            // Make sure submodules of a local checkout are populated
+            if self.recurse_submodules && path.join(".gitmodules").is_file() {
+                let status = Command::new("git")
+                    .arg("-C")
+                    .arg(&path)
+                    .args(["submodule", "update", "--init", "--recursive"])
+                    .status();
+                if !status.is_ok_and(|s| s.success()) {
+                    warn!("Unable to initialize submodules in {}", repo_path);
+                }
+            }
+            path
+        };
+
+        // Generate repository structure
+        self.attributes = GitAttributes::load(&repo_dir);
+        self.submodules = load_submodules(&repo_dir);
+        // Later files take precedence: .gitconcatignore, then .concatignore, then --concatignore
+        let mut pattern_files = vec![repo_dir.join(ConcatIgnore::ROOT_FILE_NAME), repo_dir.join(ConcatIgnore::FILE_NAME)];
+        pattern_files.extend(self.concatignore.clone());
+        let root_ignore = ConcatIgnore::load(&repo_dir, Path::new(""), &pattern_files);
+        // Preset patterns have the lowest precedence, --exclude/--include the highest
+        let preset_ignore = self.preset.and_then(|preset| ConcatIgnore::from_patterns(&repo_dir, preset.patterns()));
+        self.explicit_ignore = ConcatIgnore::from_patterns(&repo_dir, self.patterns.iter().map(String::as_str));
+        if self.show_ignores {
+            status!("{}", self.describe_ignores(&pattern_files));
+            return Ok(Document::default());
+        }
+        let ignores: Vec<&ConcatIgnore> = preset_ignore.iter().chain(root_ignore.iter()).collect();
+        let mut structure = self.get_file_structure(&repo_dir, Path::new(""), &ignores)?;
+        for path in &self.paths {
+            if !repo_dir.join(path).exists() {
+                warn!("Path filter '{}' does not exist in the repository", path);
+            }
+        }
+
+        // Narrow the structure down to the selected files
+        let saved = self.selection.as_deref().map(Selection::load).transpose()?;
+        let chosen = if self.interactive {
+            Some(selection::interactive(&structure, saved.as_ref())?)
+        } else {
+            saved
+        };
+        if let Some(path) = &self.save_selection {
+            match &chosen {
+                Some(chosen) => chosen.save(path)?,
+                None => Selection::from_structure(&structure).save(path)?,
+            }
+        }
+        if let Some(chosen) = chosen {
+            structure = chosen.apply(structure);
+        }
+
+        // Only keep files that changed since the requested ref
+        let changes = self.since.as_deref().map(|since| changed_files(&repo_dir, since)).transpose()?;
+        if let Some(changes) = &changes {
+            structure = changes.changed.apply(structure);
+        }
+
+        // Files last modified before the cutoff stay in the structure, without contents
+        if let Some(cutoff) = self.modified_since {
+            let times = commit_times(&repo_dir);
+            let stale = mark_stale(&mut structure, &repo_dir, &times, cutoff);
+            info!("{} files were last modified before the --modified-since cutoff and are listed without contents", stale);
+        }
+
+        // Directory totals reflect the included files only; lines are added once contents are read
+        roll_up(&mut structure);
+        if let Some(count) = self.top {
+            print_top(&structure, count);
+        }
+
+        // Warn early when the input alone is larger than the output limit
+        if let Some(max) = self.max_output_size.filter(|_| !self.structure_only) {
+            let (_, total_size) = count_files(&structure);
+            if total_size > max {
+                warn!(
+                    "Selected files total {} bytes, more than the output limit of {} bytes",
+                    total_size, max
+                );
+            }
+        }
+
+        if self.flatten_paths {
+            self.colliding_names = colliding_file_names(&structure);
+        }
+        if self.format == OutputFormat::Markdown {
+            let mut anchors = HashMap::new();
+            self.file_anchors(&structure, &mut HashSet::new(), &mut anchors);
+            self.anchors = anchors;
+        }
+
+        // Process all files first, since processing adds details (e.g. encodings) to the structure
+        let mut contents = String::new();
+        let mut state = ProcessState { stream, ..Default::default() };
+        let mut walk_errors = std::mem::take(self.walk_errors.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner()));
+        // Entries are pushed as the walk threads reach them
+        walk_errors.sort();
+        for (path, error) in walk_errors {
+            state.report.add(&path, Reason::Unreadable, Some(error));
+        }
+        if !self.structure_only {
+            match self.order {
+                ContentOrder::Path => self.process_files(&mut structure, &repo_dir, &mut contents, &mut state)?,
+                ContentOrder::Priority => {
+                    // Only the sections are reordered; the structure keeps its order
+                    let mut files = Vec::new();
+                    collect_files(&mut structure, &mut files);
+                    files.sort_by_cached_key(|entry| {
+                        let dir = entry.path.rsplit_once('/').map_or("", |(dir, _)| dir).to_string();
+                        (priority_tier(&entry.path), dir, entry.name.clone())
+                    });
+                    for entry in files {
+                        self.process_entry(entry, &repo_dir, &mut contents, &mut state)?;
+                    }
+                }
+            }
+        }
+
+        // Deleted files have no content left, just a note
+        let deleted = changes.iter().flat_map(|changes| &changes.deleted).filter(|_| !self.structure_only);
+        for path in deleted {
+            match self.format {
+                OutputFormat::Markdown => {
+                    state.breaks.push(contents.len());
+                    contents.push_str(&format!("## {}\n\n[deleted]\n\n", escape_markdown(&self.prefixed_path(path))));
+                }
+                OutputFormat::Text => {
+                    state.breaks.push(contents.len());
+                    contents.push_str(&format!("===== {} =====\n\n[deleted]\n\n", self.prefixed_path(path)));
+                }
+                OutputFormat::Jsonl => {
+                    let record = FileRecord {
+                        path: &self.prefixed_path(path),
+                        language: &self.get_language_from_ext(Path::new(path)),
+                        size: None,
+                        sha256: None,
+                        content: None,
+                        encoding: None,
+                        skipped_reason: Some("Deleted".to_string()),
+                    };
+                    contents.push_str(&serde_json::to_string(&record)?);
+                    contents.push('\n');
+                }
+                OutputFormat::Json => unreachable!("--format json only emits the structure"),
+            }
+        }
+
+        // List the files left out after reaching the size limit
+        if !state.omitted.is_empty() {
+            for path in &state.omitted {
+                state.report.add(path, Reason::OutputLimit, None);
+            }
+            match self.format {
+                OutputFormat::Markdown => {
+                    state.breaks.push(contents.len());
+                    contents.push_str(
+                        "# Omitted Files\n\nThe output size limit was reached; these files were not included:\n\n",
+                    );
+                    for path in &state.omitted {
+                        contents.push_str(&format!("- {}\n", escape_markdown(&self.prefixed_path(path))));
+                    }
+                    contents.push('\n');
+                }
+                OutputFormat::Text => {
+                    state.breaks.push(contents.len());
+                    contents.push_str(
+                        "===== Omitted Files =====\n\nThe output size limit was reached; these files were not included:\n\n",
+                    );
+                    for path in &state.omitted {
+                        contents.push_str(&format!("{}\n", self.prefixed_path(path)));
+                    }
+                    contents.push('\n');
+                }
+                OutputFormat::Jsonl => {
+                    for path in &state.omitted {
+                        let record = FileRecord {
+                            path: &self.prefixed_path(path),
+                            language: &self.get_language_from_ext(Path::new(path)),
+                            size: None,
+                            sha256: None,
+                            content: None,
+                            encoding: None,
+                            skipped_reason: Some("Omitted: output size limit reached".to_string()),
+                        };
+                        contents.push_str(&serde_json::to_string(&record)?);
+                        contents.push('\n');
+                    }
+                }
+                OutputFormat::Json => unreachable!("--format json only emits the structure"),
+            }
+        }
+
+        // JSON Lines output ends with a summary record
+        if self.format == OutputFormat::Jsonl {
+            let summary = SummaryRecord {
+                record_type: "summary",
+                files: state.languages.values().map(|l| l.files).sum(),
+                bytes: state.languages.values().map(|l| l.bytes).sum(),
+                skipped: state.report.count(Reason::Binary),
+                generated: state.generated,
+                duplicates: state.duplicates,
+                normalized: state.normalized,
+                deleted: changes.as_ref().map_or(0, |changes| changes.deleted.len()),
+                omitted: state.omitted.len(),
+            };
+            contents.push_str(&serde_json::to_string(&summary)?);
+            contents.push('\n');
+        }
+
+        // The trailing parts (deleted, omitted, summary) complete the stream; anything before it is written separately
+        if let Some(stream) = state.stream.as_mut() {
+            stream.write(&contents)?;
+            contents.clear();
+        }
+
+        if !self.structure_only {
+            roll_up(&mut structure);
+        }
+        self.prefix_structure(&mut structure);
+
+        // JSON Lines output consists of file records only
+        let mut markdown = String::new();
+        if self.format == OutputFormat::Json {
+            markdown.push_str(&self.structure_json(&structure)?);
+            markdown.push('\n');
+        }
+        if self.format == OutputFormat::Markdown {
+            if self.front_matter {
+                markdown.push_str(&self.front_matter(repo_path, &repo_dir, &structure)?);
+            }
+            if let (Some(since), Some(changes)) = (&self.since, &changes) {
+                markdown.push_str(&format!("# Changes Since {}\n\n```\n{}```\n\n", since, changes.stat));
+            }
+            if !self.no_structure {
+                markdown.push_str("# Repository Structure\n\n```json\n");
+                markdown.push_str(&self.structure_json(&structure)?);
+                markdown.push_str("\n```\n\n");
+            }
+            if self.stats {
+                markdown.push_str(&render_stats(&state.languages, state.generated, state.normalized));
+            }
+            if self.toc {
+                markdown.push_str(&self.render_toc(&state.sections));
+            }
+            if !self.structure_only {
+                markdown.push_str("# File Contents\n\n");
+            }
+        }
+        if self.format == OutputFormat::Text {
+            if self.front_matter {
+                markdown.push_str(&self.front_matter(repo_path, &repo_dir, &structure)?);
+            }
+            if let (Some(since), Some(changes)) = (&self.since, &changes) {
+                markdown.push_str(&format!("===== Changes Since {} =====\n\n{}\n", since, changes.stat));
+            }
+            if !self.no_structure {
+                markdown.push_str("===== Repository Structure =====\n\n");
+                markdown.push_str(&self.structure_json(&structure)?);
+                markdown.push_str("\n\n");
+            }
+            if self.stats {
+                markdown.push_str(&render_stats(&state.languages, state.generated, state.normalized));
+            }
+        }
+        let breaks = state.breaks.iter().map(|offset| markdown.len() + offset).collect();
+        for file in state.files.values_mut() {
+            file.offset += markdown.len();
+        }
+        markdown.push_str(&contents);
+
+        // The structure and metadata count towards the limit too
+        if let Some(max) = self.max_output_size {
+            if self.on_limit == OnLimit::Abort && state.streamed() + markdown.len() as u64 > max {
+                return Err(ConcatError::OutputTooLarge { limit: max });
+            }
+        }
+
+        // Part of the run summary, next to the "Successfully generated" line
+        if self.dedup && state.duplicates > 0 {
+            status!("Collapsed {} duplicate files ({} bytes saved)", state.duplicates, state.bytes_saved);
+        }
+        if self.normalize_eol != LineEndings::Keep {
+            info!("Normalized the line endings of {} files", state.normalized);
+        }
+        if self.previous.is_some() {
+            info!("Reused {} of {} file sections from the previous output", state.reused, state.files.len());
+        }
+
+        // Summarize warnings instead of printing one line per file
+        let hint = if log::log_enabled!(log::Level::Debug) { "" } else { " (rerun with -vv for the list)" };
+        if let Some(summary) = state.report.summary(|reason| reason.is_warning()) {
+            warn!("{}{}", summary, hint);
+        }
+        if let Some(summary) = state.report.summary(|reason| !reason.is_warning()) {
+            info!("{}", summary);
+        }
+
+        // Nothing is written when the audit found something and --fail-on-audit was given
+        if self.auditor.is_some() {
+            let mut excluded_env = std::mem::take(self.excluded_env.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner()));
+            excluded_env.sort();
+            eprint!("{}", audit::render(&state.findings, &excluded_env));
+            if self.fail_on_audit && !state.findings.is_empty() {
+                return Err(ConcatError::AuditFailed(state.findings.len()));
+            }
+        }
+
+        let sidecar = self.manifest.then(|| {
+            let files = state.files.iter().map(|(path, file)| SidecarFile {
+                path: path.clone(),
+                language: self.get_language_from_ext(Path::new(path)),
+                size: file.size,
+                lines: file.lines,
+                sha256: file.sha256.clone(),
+                offset: file.offset,
+                length: file.length,
+            });
+            let skipped = state.report.entries.iter().filter(|entry| entry.reason.skips_content()).map(|entry| Skipped {
+                path: entry.path.clone(),
+                reason: entry.reason,
+                detail: entry.detail.clone(),
+            });
+            let commit = git_output(&repo_dir, &["rev-parse", "HEAD"]);
+            Sidecar::new(repo_name_from_path(repo_path), commit, files.collect(), skipped.collect())
+        });
+
+        Ok(Document { text: markdown, breaks, report: state.report, files: state.files, sidecar })
+    }
+
+    /// The effective ignore rules for --show-ignores, from the built-in lists to --exclude/--include
+    fn describe_ignores(&self, pattern_files: &[PathBuf]) -> String {
+        let sorted = |set: &HashSet<String>| {
+            let mut items: Vec<_> = set.iter().map(String::as_str).collect();
+            items.sort_unstable();
+            items.join(", ")
+        };
+        let mut text = String::from("Effective ignore rules (later rules take precedence):\n\n");
+        text.push_str(&format!("Built-in directories: {}\n", self.ignore_dirs.sorted().join(", ")));
+        match self.hidden {
+            HiddenPolicy::Default => {
+                text.push_str(&format!("Built-in dot-directories: {}\n", self.ignore_hidden_dirs.sorted().join(", ")))
+            }
+            HiddenPolicy::Include => text.push_str("Dotfiles and dot-directories: included (--hidden)\n"),
+            HiddenPolicy::Exclude => text.push_str("Dotfiles and dot-directories: excluded (--no-hidden)\n"),
+        }
+        text.push_str(&format!("Built-in files: {}\n", self.ignore_files.sorted().join(", ")));
+        if !self.include_env {
+            text.push_str(".env* files: excluded (--include-env keeps them)\n");
+        }
+        match &self.only_ext {
+            Some(only_ext) => text.push_str(&format!("Only extensions (--only-ext): {}\n", sorted(only_ext))),
+            None => text.push_str(&format!("Binary extensions: {}\n", sorted(&self.ignore_extensions))),
+        }
+
+        let mut section = |title: String, patterns: Vec<String>| {
+            text.push_str(&format!("\n{}:\n", title));
+            for pattern in patterns {
+                text.push_str(&format!("  {}\n", pattern));
+            }
+        };
+        if let Some(preset) = self.preset {
+            section(format!("Preset {} ({})", preset.name, preset.description), preset.patterns().map(String::from).collect());
+        }
+        for file in pattern_files.iter().filter(|file| file.is_file()) {
+            let patterns = fs::read_to_string(file).unwrap_or_default();
+            let patterns = patterns.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+            section(file.display().to_string(), patterns.map(String::from).collect());
+        }
+        if !self.patterns.is_empty() {
+            section("--exclude/--include".to_string(), self.patterns.clone());
+        }
+
+        text.push_str("\nNested .concatignore files apply to their own directories.\n");
+        for rule in &self.limits {
+            text.push_str(&format!("Matching files over the limit are omitted (--limit {}).\n", rule.rule));
+        }
+        if let Some(max_lines) = self.max_lines {
+            text.push_str(&format!("Files with more than {} lines are omitted (--max-lines).\n", max_lines));
+        }
+        text
+    }
+
+    /// Path as emitted in the output, with the --path-prefix applied
+    fn prefixed_path(&self, path: &str) -> String {
+        match &self.path_prefix {
+            Some(prefix) => format!("{}{}", prefix, path),
+            None => path.to_string(),
+        }
+    }
+
+    /// Path shown in a markdown file heading, escaped: with --flatten-paths only the file name, followed by
+    /// its directory when several files share that name (e.g. `mod.rs (src/parser/)`)
+    fn heading_path(&self, path: &str) -> String {
+        let path = self.prefixed_path(path);
+        if !self.flatten_paths {
+            return escape_markdown(&path);
+        }
+        let (dir, name) = path.rsplit_once('/').unwrap_or((".", &path));
+        if self.colliding_names.contains(name) {
+            format!("{} ({}/)", escape_markdown(name), escape_markdown(dir))
+        } else {
+            escape_markdown(name)
+        }
+    }
+
+    /// The `## path` heading of a file section, preceded by the file's anchor
+    fn file_heading(&self, path: &str) -> String {
+        match self.anchors.get(path) {
+            Some(anchor) => format!("<a id=\"{}\"></a>\n\n## {}", anchor, self.heading_path(path)),
+            None => format!("## {}", self.heading_path(path)),
+        }
+    }
+
+    /// Table of contents with a link to the anchor of every file section, in document order
+    fn render_toc(&self, sections: &[String]) -> String {
+        let mut toc = String::from("# Table of Contents\n\n");
+        for path in sections {
+            if let Some(anchor) = self.anchors.get(path) {
+                toc.push_str(&format!("- [{}](#{})\n", self.heading_path(path), anchor));
+            }
+        }
+        toc.push('\n');
+        toc
+    }
+
+    /// Assigns every file in the structure a unique anchor id such as `file-src-main-rs`.
+    /// Paths that slugify alike get `-2`, `-3`, ... in structure order, so ids are stable between runs.
+    fn file_anchors(&self, entries: &[FileEntry], used: &mut HashSet<String>, anchors: &mut HashMap<String, String>) {
+        for entry in entries {
+            if let Some(children) = &entry.children {
+                self.file_anchors(children, used, anchors);
+                continue;
+            }
+            let base = format!("file-{}", slugify(&self.prefixed_path(&entry.path)));
+            let mut anchor = base.clone();
+            let mut n = 1;
+            while !used.insert(anchor.clone()) {
+                n += 1;
+                anchor = format!("{}-{}", base, n);
+            }
+            anchors.insert(entry.path.clone(), anchor);
+        }
+    }
+
+    /// Applies the --path-prefix to every path in the structure (once the files have been read)
+    fn prefix_structure(&self, entries: &mut [FileEntry]) {
+        for entry in entries {
+            entry.path = self.prefixed_path(&entry.path);
+            if let Some(children) = &mut entry.children {
+                self.prefix_structure(children);
+            }
+        }
+    }
+
+    /// Serializes the structure, limited to the selected fields if any
+    fn structure_json(&self, structure: &[FileEntry]) -> Result<String, ConcatError> {
+        match &self.structure_fields {
+            Some(fields) => {
+                let projected: Vec<_> = structure.iter().map(|entry| self.project_entry(entry, fields)).collect();
+                Ok(serde_json::to_string_pretty(&projected)?)
+            }
+            None => Ok(serde_json::to_string_pretty(structure)?),
+        }
+    }
+
+    /// Projects an entry onto the selected fields (children are always kept to preserve nesting)
+    fn project_entry(&self, entry: &FileEntry, fields: &[StructureField]) -> serde_json::Value {
+        let mut object = serde_json::Map::new();
+        for field in fields {
+            let value = match field {
+                StructureField::Type => Some(serde_json::Value::from(entry.entry_type.as_str())),
+                StructureField::Name => Some(serde_json::Value::from(entry.name.as_str())),
+                StructureField::Path => Some(serde_json::Value::from(entry.path.as_str())),
+                StructureField::Size => entry.size.map(serde_json::Value::from),
+                StructureField::Mode => entry.mode.as_deref().map(serde_json::Value::from),
+                StructureField::Language => entry
+                    .children
+                    .is_none()
+                    .then(|| self.get_language_from_ext(Path::new(&entry.path)))
+                    .filter(|lang| !lang.is_empty())
+                    .map(serde_json::Value::from),
+                StructureField::Encoding => entry.encoding.as_deref().map(serde_json::Value::from),
+                StructureField::Hash => entry.hash.as_deref().map(serde_json::Value::from),
+            };
+            if let Some(value) = value {
+                object.insert(field.key().to_string(), value);
+            }
+        }
+        if let Some(children) = &entry.children {
+            let children = children.iter().map(|child| self.project_entry(child, fields)).collect();
+            object.insert("children".to_string(), serde_json::Value::Array(children));
+        }
+        serde_json::Value::Object(object)
+    }
+
+    /// Builds the YAML front matter block describing the generated document
+    fn front_matter(&self, repo_path: &str, repo_dir: &Path, structure: &[FileEntry]) -> Result<String, ConcatError> {
+        let (file_count, total_size) = count_files(structure);
+        let branch = git_output(repo_dir, &["rev-parse", "--abbrev-ref", "HEAD"]).filter(|b| b != "HEAD");
+        let metadata = FrontMatter {
+            repository: &repo_name_from_path(repo_path),
+            source: &redact_url(repo_path),
+            commit: git_output(repo_dir, &["rev-parse", "HEAD"]),
+            branch,
+            generated_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            file_count,
+            total_size,
+        };
+        Ok(format!("---\n{}---\n\n", serde_yaml::to_string(&metadata)?))
+    }
+
+    /// Recursively processes files and adds their contents to the markdown document
+    fn process_files(
+        &self,
+        entries: &mut [FileEntry],
+        base_dir: &Path,
+        markdown: &mut String,
+        state: &mut ProcessState,
+    ) -> Result<(), ConcatError> {
+        for entry in entries.iter_mut() {
+            if let Some(ref mut children) = entry.children {
+                // Recursively process directory contents (submodule placeholders have none)
+                self.process_files(children, base_dir, markdown, state)?;
+            } else {
+                self.process_entry(entry, base_dir, markdown, state)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Processes one file and adds its section to the document
+    fn process_entry(
+        &self,
+        entry: &mut FileEntry,
+        base_dir: &Path,
+        markdown: &mut String,
+        state: &mut ProcessState,
+    ) -> Result<(), ConcatError> {
+        // Files older than --modified-since are only listed in the structure
+        if entry.stale.is_some() {
+            return Ok(());
+        }
+
+        // Once the output limit is reached, remaining files are only listed, however small
+        if state.limit_reached {
+            state.omitted.push(entry.path.clone());
+            return Ok(());
+        }
+
+        // Process file contents
+        let full_path = base_dir.join(&entry.path);
+
+        // Unchanged files keep the section of the previous run (--incremental)
+        let mtime = self.incremental.then(|| incremental::modified(&full_path)).flatten();
+        let heading = self.section_heading(&entry.path);
+        let events = state.report.entries.len();
+        let previous = self.previous.as_ref().and_then(|previous| {
+            previous.section(&entry.path, entry.size, mtime, &heading)
+        });
+        let (section, lines) = match previous {
+            Some((file, section)) => {
+                debug!("Reusing the section of {}", entry.path);
+                state.reused += 1;
+                entry.encoding = file.encoding.clone();
+                entry.classification = file.classification.clone();
+                entry.hash = file.sha256.clone();
+                if file.classification.is_some() {
+                    state.generated += 1;
+                }
+                for event in &file.events {
+                    state.report.add(&entry.path, event.reason, event.detail.clone());
+                }
+                (section.to_string(), file.lines)
+            }
+            None => self.process_file(entry, base_dir, &full_path, state)?,
+        };
+
+        // Enforce the output size limit at file boundaries
+        let total = state.streamed() + (markdown.len() + section.len()) as u64;
+        if self.max_output_size.is_some_and(|max| total > max) {
+            match self.on_limit {
+                OnLimit::Abort => {
+                    return Err(ConcatError::OutputTooLarge { limit: self.max_output_size.unwrap_or_default() })
+                }
+                OnLimit::Truncate => {
+                    state.limit_reached = true;
+                    state.omitted.push(entry.path.clone());
+                    return Ok(());
+                }
+            }
+        }
+
+        // Record per-language statistics (files omitted at the limit don't count)
+        let stats = state.languages.entry(self.language_bucket(&full_path)).or_default();
+        stats.files += 1;
+        stats.bytes += entry.size.unwrap_or(0);
+        stats.lines += lines;
+
+        // Offsets count the bytes actually written, including those already streamed to the output file
+        let offset = state.streamed() as usize + markdown.len();
+        state.emit(markdown, &section)?;
+        state.sections.push(entry.path.clone());
+        entry.lines = Some(lines);
+
+        if self.incremental || self.manifest {
+            let sha256 = if self.hash { entry.hash.clone() } else { entry.hash.take() };
+            let file = FileState {
+                size: entry.size.unwrap_or(0),
+                mtime: mtime.unwrap_or(0),
+                sha256,
+                heading,
+                offset,
+                length: section.len(),
+                lines,
+                encoding: entry.encoding.clone(),
+                classification: entry.classification.clone(),
+                events: state.report.entries[events..].to_vec(),
+            };
+            state.files.insert(entry.path.clone(), file);
+        }
+        Ok(())
+    }
+
+    /// Reads and renders the section of one file, returning it with the file's number of text lines
+    fn process_file(
+        &self,
+        entry: &mut FileEntry,
+        base_dir: &Path,
+        full_path: &Path,
+        state: &mut ProcessState,
+    ) -> Result<(String, usize), ConcatError> {
+        let mut lang = self.get_language_from_ext(full_path);
+
+        // Entries the walk could not read only get a placeholder (they are already in the report)
+        if let Some(error) = &entry.error {
+            return Ok((self.render_file(entry, &lang, FileContent::Unreadable(error.clone()))?, 0));
+        }
+
+        let mut content = self.read_content(full_path, entry, state)?;
+
+        // The audit sees the contents as read, before filtering or shortening
+        if let Some(auditor) = &self.auditor {
+            let text = match &content {
+                FileContent::Text(text) => Some(text.as_str()),
+                _ => None,
+            };
+            state.findings.extend(auditor.scan(&entry.path, entry.size.unwrap_or(0), text));
+        }
+
+        // Formats such as notebooks are converted to readable text; a failed conversion keeps the file as it is
+        if let (Some(converter), FileContent::Text(text)) = (convert::for_file(&entry.name), &mut content) {
+            match converter.convert(text) {
+                Ok(conversion) => {
+                    *text = conversion.text;
+                    lang = conversion.language.to_string();
+                }
+                Err(e) => warn!("Including {} unconverted: {}", entry.path, e),
+            }
+        }
+
+        // Text is piped through --filter-cmd; when the command fails the content stays as it is
+        if let (Some(command), FileContent::Text(text)) = (&self.filter_cmd, &mut content) {
+            match run_filter(command, base_dir, &entry.path, text) {
+                Ok(filtered) => *text = filtered,
+                Err(e) => {
+                    debug!("--filter-cmd failed for {}: {}", entry.path, e);
+                    state.report.add(&entry.path, Reason::FilterFailed, Some(e));
+                }
+            }
+        }
+
+        // Unify line endings of text contents (binary contents are never touched)
+        if let FileContent::Text(text) = &mut content {
+            if let Some(converted) = self.normalize_eol.apply(text) {
+                *text = converted;
+                state.normalized += 1;
+            }
+        }
+
+        let mut lines = 0;
+        if let FileContent::Text(text) = &content {
+            lines = text.lines().count();
+
+            // Files over their limit only get a placeholder: the first matching --limit rule, else --max-lines
+            match LimitRule::first_match(&self.limits, &entry.path) {
+                Some(rule) => {
+                    if let Some(exceeded) = rule.exceeded(entry.size.unwrap_or(text.len() as u64), lines) {
+                        debug!("Omitting {} ({}, limit {})", entry.path, exceeded, rule.rule);
+                        let reason = match rule.limit {
+                            Limit::Bytes(_) => Reason::TooLarge,
+                            Limit::Lines(_) => Reason::TooManyLines,
+                        };
+                        state.report.add(&entry.path, reason, Some(format!("{} (limit {})", exceeded, rule.rule)));
+                        content = FileContent::OverLimit(format!("{} (limit {})", exceeded, rule.rule));
+                    }
+                }
+                None if self.max_lines.is_some_and(|max| lines > max) => {
+                    debug!("Omitting {} ({} lines)", entry.path, lines);
+                    state.report.add(&entry.path, Reason::TooManyLines, Some(format!("{} lines", lines)));
+                    content = FileContent::TooLong(lines);
+                }
+                None => {}
+            }
+        }
+
+        // Long files are shortened to their head and tail (--max-lines takes precedence)
+        if let (Some(keep), FileContent::Text(text)) = (self.preview_lines, &mut content) {
+            if let Some(preview) = preview_lines(text, keep) {
+                debug!("Shortening {} to a preview ({} lines)", entry.path, lines);
+                state.report.add(&entry.path, Reason::Previewed, Some(format!("{} lines", lines)));
+                *text = preview;
+            }
+        }
+
+        if self.dedup {
+            content = self.deduplicate(content, entry, state);
+        }
+
+        Ok((self.render_file(entry, &lang, content)?, lines))
+    }
+
+    /// Heading that identifies a file's section; a reused section must have been rendered with the same one
+    fn section_heading(&self, path: &str) -> String {
+        match self.format {
+            OutputFormat::Markdown => self.file_heading(path),
+            _ => self.prefixed_path(path),
+        }
+    }
+
+    /// Renders the output section (markdown) or record (JSON Lines) for one file
+    fn render_file(&self, entry: &FileEntry, lang: &str, content: FileContent) -> Result<String, ConcatError> {
+        let mut section = String::new();
+        let path = self.prefixed_path(&entry.path);
+        match self.format {
+            OutputFormat::Markdown | OutputFormat::Text => {
+                // Plain text sections start with a `===== path =====` separator and have no fences
+                let plain = self.format == OutputFormat::Text;
+                let heading = |suffix: &str| {
+                    if plain {
+                        format!("===== {}{} =====", path, suffix)
+                    } else {
+                        format!("{}{}", self.file_heading(&entry.path), suffix)
+                    }
+                };
+                // Converted files (notebooks) contain fences of their own, so theirs is longer than any backtick run
+                let fence = match &content {
+                    FileContent::Text(text) if convert::for_file(&entry.name).is_some() => convert::fence(text),
+                    _ => "```".to_string(),
+                };
+                let (open, close) =
+                    if plain { (String::new(), "\n\n".to_string()) } else { (format!("{}{}\n", fence, lang), format!("\n{}\n\n", fence)) };
+
+                // Collapsed files only get a one-line section
+                match &content {
+                    FileContent::Duplicate(first_path) => {
+                        let first_path = if plain { self.prefixed_path(first_path) } else { self.heading_path(first_path) };
+                        return Ok(format!("{}\n\n", heading(&format!(" — identical to {}", first_path))));
+                    }
+                    FileContent::Text(text) if self.dedup && text.is_empty() => {
+                        return Ok(format!("{}\n\n", heading(" — (empty file)")));
+                    }
+                    // Images are HTML, which markdown renderers display in place
+                    FileContent::Image(mime_type, bytes) if !plain => {
+                        let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+                        let alt = path.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;").replace('>', "&gt;");
+                        return Ok(format!("{}\n\n<img src=\"data:{};base64,{}\" alt=\"{}\">\n\n", heading(""), mime_type, data, alt));
+                    }
+                    _ => {}
+                }
+
+                // Hard-wrap overlong lines if requested
+                let mut notes = Vec::new();
+                if let Some(encoding) = &entry.encoding {
+                    notes.push(format!("transcoded from {}", encoding));
+                }
+                let content = match (content, self.wrap) {
+                    (FileContent::Text(text), Some(width)) => match wrap_long_lines(&text, width) {
+                        Some(wrapped) => {
+                            notes.push(format!("long lines wrapped at {} characters", width));
+                            FileContent::Text(wrapped)
+                        }
+                        None => FileContent::Text(text),
+                    },
+                    (content, _) => content,
+                };
+
+                // Add file header and content
+                if notes.is_empty() {
+                    section.push_str(&format!("{}\n\n", heading("")));
+                } else {
+                    section.push_str(&format!("{}\n\n", heading(&format!(" ({})", notes.join(", ")))));
+                }
+                match content {
+                    FileContent::Text(text) => {
+                        section.push_str(&open);
+                        section.push_str(&text);
+                    }
+                    FileContent::Binary(bytes) | FileContent::Image(_, bytes) => {
+                        section.push_str(&format!(
+                            "Base64-encoded binary file ({} bytes)\nsha256: {}\n\n{}",
+                            bytes.len(),
+                            sha256_hex(&bytes),
+                            if plain { "" } else { "```base64\n" }
+                        ));
+                        let encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
+                        for line in encoded.as_bytes().chunks(76) {
+                            section.push_str(&String::from_utf8_lossy(line));
+                            section.push('\n');
+                        }
+                        section.pop();
+                    }
+                    FileContent::Skipped(_) => {
+                        section.push_str(&open);
+                        section.push_str("[Binary or non-UTF8 file content skipped]");
+                    }
+                    FileContent::Generated(size) => {
+                        section.push_str(&open);
+                        section.push_str(&format!("[generated/minified file skipped, {} bytes]", size));
+                    }
+                    FileContent::TooLong(lines) => {
+                        section.push_str(&open);
+                        section.push_str(&format!("[File omitted: {} lines]", lines));
+                    }
+                    FileContent::OverLimit(exceeded) => {
+                        section.push_str(&open);
+                        section.push_str(&format!("[File omitted: {}]", exceeded));
+                    }
+                    FileContent::Unreadable(error) => {
+                        section.push_str(&open);
+                        section.push_str(&format!("[File unreadable: {}]", error));
+                    }
+                    FileContent::Duplicate(_) => unreachable!("duplicates are emitted above"),
+                }
+                section.push_str(&close);
+            }
+            OutputFormat::Json => unreachable!("--format json only emits the structure"),
+            OutputFormat::Jsonl => {
+                // Add one JSON object per file, terminated by a newline
+                let mut record = FileRecord {
+                    path: &path,
+                    language: lang,
+                    size: entry.size,
+                    sha256: None,
+                    content: None,
+                    encoding: None,
+                    skipped_reason: None,
+                };
+                match content {
+                    FileContent::Text(text) => {
+                        record.sha256 = Some(sha256_hex(text.as_bytes()));
+                        record.content = Some(text);
+                    }
+                    FileContent::Binary(bytes) | FileContent::Image(_, bytes) => {
+                        record.content = Some(base64::engine::general_purpose::STANDARD.encode(&bytes));
+                        record.encoding = Some("base64");
+                        record.sha256 = Some(sha256_hex(&bytes));
+                    }
+                    FileContent::Skipped(reason) => record.skipped_reason = Some(reason),
+                    FileContent::Generated(size) => {
+                        record.skipped_reason = Some(format!("Generated/minified file ({} bytes)", size))
+                    }
+                    FileContent::TooLong(lines) => record.skipped_reason = Some(format!("File omitted: {} lines", lines)),
+                    FileContent::OverLimit(exceeded) => record.skipped_reason = Some(format!("File omitted: {}", exceeded)),
+                    FileContent::Unreadable(error) => record.skipped_reason = Some(format!("Unreadable: {}", error)),
+                    FileContent::Duplicate(first_path) => {
+                        record.skipped_reason = Some(format!("Identical to {}", self.prefixed_path(&first_path)));
+                    }
+                }
+                section.push_str(&serde_json::to_string(&record)?);
+                section.push('\n');
+            }
+        }
+        Ok(section)
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        if let ConcatError::InvalidArguments(_) = e {
+            let program = std::env::args().next().unwrap_or_else(|| env!("CARGO_PKG_NAME").to_string());
+            eprintln!("Usage: {} <repository-path-or-url> [paths...] [options]\n\nOptions:\n{}", program, OPTIONS_HELP);
+        }
+        std::process::exit(e.exit_code());
+    }
+}
+
+/// Output file for the generated document, compressed on the fly if requested
+struct OutputWriter {
+    path: PathBuf,    // Path of the output file
+    encoder: Encoder, // Writer for the (possibly compressed) file
+    len: u64,         // Uncompressed bytes written so far
+}
+
+/// Writer behind an `OutputWriter` for each compression
+enum Encoder {
+    Plain(io::BufWriter<fs::File>),
+    Gzip(flate2::write::GzEncoder<fs::File>),
+    Zstd(zstd::stream::write::Encoder<'static, fs::File>),
+}
+
+impl std::fmt::Debug for OutputWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutputWriter").field("path", &self.path).field("len", &self.len).finish_non_exhaustive()
+    }
+}
+
+impl OutputWriter {
+    /// Creates (or truncates) the output file
+    fn create(path: &Path, compression: Option<Compression>) -> Result<Self, ConcatError> {
+        let error = |source| ConcatError::OutputWrite { path: path.to_path_buf(), source };
+        let file = fs::File::create(path).map_err(error)?;
+        let encoder = match compression {
+            None => Encoder::Plain(io::BufWriter::new(file)),
+            Some(Compression::Gzip) => {
+                Encoder::Gzip(flate2::write::GzEncoder::new(file, flate2::Compression::default()))
+            }
+            Some(Compression::Zstd) => Encoder::Zstd(zstd::stream::write::Encoder::new(file, 0).map_err(error)?),
+        };
+        Ok(Self { path: path.to_path_buf(), encoder, len: 0 })
+    }
+
+    /// Appends text to the output
+    fn write(&mut self, text: &str) -> Result<(), ConcatError> {
+        use std::io::Write;
+
+        let result = match &mut self.encoder {
+            Encoder::Plain(writer) => writer.write_all(text.as_bytes()),
+            Encoder::Gzip(writer) => writer.write_all(text.as_bytes()),
+            Encoder::Zstd(writer) => writer.write_all(text.as_bytes()),
+        };
+        result.map_err(|source| ConcatError::OutputWrite { path: self.path.clone(), source })?;
+        self.len += text.len() as u64;
+        Ok(())
+    }
+
+    /// Flushes and closes the file; returns its size on disk
+    fn finish(self) -> Result<u64, ConcatError> {
+        let path = self.path.clone();
+        self.into_file()
+            .and_then(|_| fs::metadata(&path))
+            .map(|metadata| metadata.len())
+            .map_err(|source| ConcatError::OutputWrite { path, source })
+    }
+
+    /// Flushes the output, appends the raw bytes of `tail` (written by another `OutputWriter` with the same
+    /// compression) and closes the file; returns its size on disk. Concatenated gzip members and zstd frames
+    /// decompress to the concatenated text, so the document never has to be decompressed to be assembled.
+    fn finish_with(self, tail: &Path) -> Result<u64, ConcatError> {
+        let path = self.path.clone();
+        let error = |source| ConcatError::OutputWrite { path: path.clone(), source };
+        let mut file = self.into_file().map_err(error)?;
+        let mut tail = fs::File::open(tail).map_err(|e| ConcatError::io(tail, e))?;
+        io::copy(&mut tail, &mut file).map_err(error)?;
+        file.sync_all().map_err(error)?;
+        Ok(fs::metadata(&path).map_err(error)?.len())
+    }
+
+    /// Flushes the encoder and returns the underlying file
+    fn into_file(self) -> io::Result<fs::File> {
+        match self.encoder {
+            Encoder::Plain(writer) => writer.into_inner().map_err(|e| e.into_error()),
+            Encoder::Gzip(writer) => writer.finish(),
+            Encoder::Zstd(writer) => writer.finish(),
+        }
+    }
+}
+
+/// Largest image shown inline with --embed-images (100 KB)
+const MAX_EMBEDDED_IMAGE: u64 = 100 * 1024;
+
+/// Largest document copied to the clipboard (16 MB)
+const MAX_CLIPBOARD_SIZE: usize = 16 * 1024 * 1024;
+
+/// Puts text on the system clipboard
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(text: &str) -> Result<(), ConcatError> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| ConcatError::Clipboard(e.to_string()))?;
+    clipboard.set_text(text).map_err(|e| ConcatError::Clipboard(e.to_string()))
+}
+
+/// Puts text on the system clipboard (unsupported in this build)
+#[cfg(not(feature = "clipboard"))]
+fn copy_to_clipboard(_text: &str) -> Result<(), ConcatError> {
+    Err(ConcatError::Clipboard("built without the `clipboard` feature".to_string()))
+}
+
+/// Parses arguments, generates the document and writes it to the output directory
+fn run() -> Result<(), ConcatError> {
+    // Get command line arguments
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = Config::from_args(&args).map_err(ConcatError::InvalidArguments)?;
+    logging::init(config.log_level);
+
+    if config.clear_cache {
+        let cache_dir = config.cache_dir.clone().unwrap_or_else(default_cache_dir);
+        match fs::remove_dir_all(&cache_dir) {
+            Ok(()) => status!("Cleared clone cache {}", cache_dir.display()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(ConcatError::io(&cache_dir, e)),
+        }
+        if config.repo_path.is_empty() {
+            return Ok(());
+        }
+    }
+
+    // Create output directory if it doesn't exist (--show-ignores only prints)
+    let output_dir = Path::new("./output");
+    if !config.show_ignores {
+        fs::create_dir_all(output_dir).map_err(|source| ConcatError::OutputWrite { path: output_dir.to_path_buf(), source })?;
+    }
+
+    let repo_name = repo_name_from_path(&config.repo_path);
+
+    let mut processor = FileProcessor::new(&config);
+    processor.validate_language_filters()?;
+
+    // Create output file path
+    let output_file = |stem: &str| {
+        let mut file_name = format!("{}.{}", stem, config.format.extension());
+        if let Some(compression) = config.compress {
+            file_name = format!("{}.{}", file_name, compression.extension());
+        }
+        output_dir.join(file_name)
+    };
+    let timestamp = config.timestamp.then(|| chrono::Local::now().format("%Y%m%dT%H%M%S").to_string());
+    let stem = output_stem(&repo_name, timestamp.as_deref(), |stem| {
+        output_file(stem).exists() || output_file(&format!("{}.part1", stem)).exists()
+    });
+    let output_path = (!config.no_file && !config.show_ignores).then(|| output_file(&stem));
+
+    // Refuse to overwrite before doing any work (split parts are also checked as they are written)
+    let replaced = output_path.as_ref().is_some_and(|path| path.exists());
+    if replaced && config.no_clobber && config.split.is_none() {
+        return Err(already_exists(output_path.unwrap_or_default()));
+    }
+
+    // With --incremental, sections of unchanged files are taken from the previous output
+    // Rules from --config change the document as well
+    let limit_rules = config.limits.iter().map(|rule| format!("--limit={}", rule.rule));
+    let options = incremental::fingerprint(&args.iter().cloned().chain(limit_rules).collect::<Vec<_>>());
+    if let (true, Some(path)) = (config.incremental, &output_path) {
+        match Previous::load(path, &options) {
+            Ok(previous) => processor.previous = Some(previous),
+            Err(reason) => status!("Full rebuild: {}", reason),
+        }
+    }
+
+    // Compressed markdown and text sections are streamed through the encoder into a temporary file next to the
+    // output, since the structure in front of them is only complete at the end (it is prepended as its own
+    // gzip member or zstd frame). The uncompressed document is never written or held in memory as a whole.
+    let streamed_sections = config.compress.is_some()
+        && matches!(config.format, OutputFormat::Markdown | OutputFormat::Text)
+        && !config.clipboard
+        && config.split.is_none();
+    let sections_file = match &output_path {
+        Some(path) if streamed_sections => Some(
+            tempfile::Builder::new()
+                .prefix(".sections-")
+                .tempfile_in(output_dir)
+                .map_err(|source| ConcatError::OutputWrite { path: path.clone(), source })?,
+        ),
+        _ => None,
+    };
+
+    // JSON Lines records are streamed to the file as soon as each one is ready
+    let mut stream = match (&output_path, &sections_file) {
+        (Some(_), Some(sections_file)) => Some(OutputWriter::create(sections_file.path(), config.compress)?),
+        (Some(path), None) if config.format == OutputFormat::Jsonl && !config.clipboard => {
+            Some(OutputWriter::create(path, config.compress)?)
+        }
+        _ => None,
     };
 
     // Process repository and generate markdown
-    let processor = FileProcessor::new();
-    let markdown = processor.generate_markdown(&args[1])?;
+    let document = match processor.generate_markdown(&config.repo_path, stream.as_mut()) {
+        Ok(document) => document,
+        Err(e) => {
+            // Don't leave a partially streamed file behind (the temporary sections file removes itself)
+            if let (Some(stream), Some(path), None) = (stream, &output_path, &sections_file) {
+                drop(stream);
+                let _ = fs::remove_file(path);
+            }
+            return Err(e);
+        }
+    };
 
-    // Create output file path
-    let output_path = format!("./output/{}.md", repo_name);
-    fs::write(&output_path, markdown)?;
-    println!("Successfully generated {}", output_path);
+    let markdown = &document.text;
+    if let Some(path) = &config.report {
+        document.report.save(path)?;
+    }
+
+    // Split output goes to <name>.part<n>.md files, cut at file boundaries
+    if let (Some(limit), Some(_)) = (config.split, &output_path) {
+        // The structure and the other front sections can't be divided, so they have to fit into the first part
+        let front = document.breaks.first().copied().unwrap_or(markdown.len());
+        if front as u64 > limit {
+            return Err(ConcatError::InvalidArguments(format!(
+                "The structure and other sections before the file contents take {} bytes, more than the --split size of {} bytes (use a larger size or --no-structure)",
+                front, limit
+            )));
+        }
+        let parts = document.split(limit, &repo_name);
+        for (index, part) in parts.iter().enumerate() {
+            let part_path = output_file(&format!("{}.part{}", stem, index + 1));
+            let part_replaced = part_path.exists();
+            if part_replaced && config.no_clobber {
+                return Err(already_exists(part_path));
+            }
+            let mut writer = OutputWriter::create(&part_path, config.compress)?;
+            writer.write(part)?;
+            writer.finish()?;
+            if part.len() as u64 > limit {
+                warn!("{} is {} bytes, over the split size (a single file is larger)", part_path.display(), part.len());
+            }
+            status!("Successfully generated {}{}", part_path.display(), replaced_note(part_replaced));
+        }
+        return check_strict(&config, &document.report);
+    }
+
+    if let Some(output_path) = &output_path {
+        let (written, size) = match (stream, &sections_file) {
+            (Some(stream), Some(sections_file)) => {
+                let size = markdown.len() as u64 + stream.len;
+                stream.finish()?;
+                let mut writer = OutputWriter::create(output_path, config.compress)?;
+                writer.write(markdown)?;
+                (writer.finish_with(sections_file.path())?, size)
+            }
+            (Some(stream), None) => {
+                let size = stream.len;
+                (stream.finish()?, size)
+            }
+            (None, _) => {
+                let mut writer = OutputWriter::create(output_path, config.compress)?;
+                writer.write(markdown)?;
+                (writer.finish()?, markdown.len() as u64)
+            }
+        };
+        if config.compress.is_some() {
+            status!(
+                "Successfully generated {} ({} bytes compressed, {} bytes uncompressed){}",
+                output_path.display(),
+                written,
+                size,
+                replaced_note(replaced)
+            );
+        } else {
+            status!("Successfully generated {}{}", output_path.display(), replaced_note(replaced));
+        }
+
+        if config.incremental {
+            Manifest::new(&options, output_path, document.files).save(&incremental::manifest_path(output_path))?;
+        }
+    }
+    if let (Some(path), Some(sidecar)) = (&config.manifest, &document.sidecar) {
+        sidecar.save(path)?;
+    }
+
+    if config.clipboard {
+        if markdown.len() > MAX_CLIPBOARD_SIZE {
+            // Very large clipboard contents can hang clipboard managers, so don't even try
+            let message = format!("document is {} bytes, over the {} byte clipboard limit", markdown.len(), MAX_CLIPBOARD_SIZE);
+            if config.no_file {
+                return Err(ConcatError::Clipboard(message));
+            }
+            warn!("Not copying to clipboard: {}", message);
+        } else {
+            copy_to_clipboard(markdown)?;
+            status!("Copied {} bytes to the clipboard", markdown.len());
+        }
+    }
+
+    check_strict(&config, &document.report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_rules_parse_sizes_and_line_counts() {
+        assert_eq!(LimitRule::parse("**/*.json=10K").unwrap().limit, Limit::Bytes(10 * 1024));
+        assert_eq!(LimitRule::parse("*.bin=1M").unwrap().limit, Limit::Bytes(1024 * 1024));
+        assert_eq!(LimitRule::parse("*.csv=2kb").unwrap().limit, Limit::Bytes(2 * 1024));
+        assert_eq!(LimitRule::parse("*.txt=500").unwrap().limit, Limit::Bytes(500));
+        assert_eq!(LimitRule::parse("**/*.snap=50lines").unwrap().limit, Limit::Lines(50));
+        assert_eq!(LimitRule::parse("*.log=1line").unwrap().limit, Limit::Lines(1));
+        assert_eq!(LimitRule::parse("*.log= 20 lines").unwrap().limit, Limit::Lines(20));
+        // The last `=` separates the limit, so patterns may contain one
+        let rule = LimitRule::parse("a=b/*.md=3lines").unwrap();
+        assert_eq!(rule.limit, Limit::Lines(3));
+        assert!(rule.matcher.is_match("a=b/x.md"));
+    }
+
+    #[test]
+    fn limit_rules_reject_invalid_suffixes() {
+        for rule in ["*.json=10X", "*.json=10KL", "*.json=lines", "*.json=tenlines", "*.json=-5lines", "*.json=", "=10K", "*.json", "[=10K"] {
+            let error = LimitRule::parse(rule).unwrap_err();
+            assert!(error.starts_with(&format!("Invalid --limit '{}'", rule)), "{}", error);
+        }
+    }
+
+    #[test]
+    fn limit_rules_first_match_wins() {
+        let rules: Vec<LimitRule> =
+            ["fixtures/**=1K", "**/*.json=10K", "**/*.json=50lines"].iter().map(|rule| LimitRule::parse(rule).unwrap()).collect();
+        // Conflicting patterns: the earlier rule decides, whichever is stricter
+        assert_eq!(LimitRule::first_match(&rules, "fixtures/data.json").unwrap().rule, "fixtures/**=1K");
+        assert_eq!(LimitRule::first_match(&rules, "src/data.json").unwrap().rule, "**/*.json=10K");
+        assert!(LimitRule::first_match(&rules, "src/main.rs").is_none());
+
+        let reversed: Vec<LimitRule> = rules.into_iter().rev().collect();
+        assert_eq!(LimitRule::first_match(&reversed, "fixtures/data.json").unwrap().rule, "**/*.json=50lines");
+    }
+
+    #[test]
+    fn limit_rules_report_how_a_file_exceeds_them() {
+        let bytes = LimitRule::parse("*.json=1K").unwrap();
+        assert_eq!(bytes.exceeded(1024, 5000), None);
+        assert_eq!(bytes.exceeded(1025, 1).as_deref(), Some("1025 bytes"));
+        let lines = LimitRule::parse("*.snap=50lines").unwrap();
+        assert_eq!(lines.exceeded(1 << 20, 50), None);
+        assert_eq!(lines.exceeded(10, 51).as_deref(), Some("51 lines"));
+    }
 
-    Ok(())
+    #[test]
+    fn cutoffs_parse_durations_back_from_now() {
+        let now = 1_800_000_000;
+        assert_eq!(parse_cutoff("12h", now), Ok(now - 12 * 3600));
+        assert_eq!(parse_cutoff("30d", now), Ok(now - 30 * 24 * 3600));
+        assert_eq!(parse_cutoff("2w", now), Ok(now - 14 * 24 * 3600));
+        assert_eq!(parse_cutoff("0d", now), Ok(now));
+    }
+
+    #[test]
+    fn cutoffs_parse_dates_and_times() {
+        let now = 1_800_000_000;
+        // Explicit offsets are exact
+        assert_eq!(parse_cutoff("2026-01-31T12:00:00Z", now), Ok(1_769_860_800));
+        assert_eq!(parse_cutoff("2026-01-31T14:00:00+02:00", now), Ok(1_769_860_800));
+        // Dates and times without an offset are local time
+        let local = |date: &str| {
+            chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+                .unwrap()
+                .and_local_timezone(chrono::Local)
+                .earliest()
+                .unwrap()
+                .timestamp()
+        };
+        assert_eq!(parse_cutoff("2026-01-31", now), Ok(local("2026-01-31 00:00:00")));
+        assert_eq!(parse_cutoff("2026-01-31T09:30:00", now), Ok(local("2026-01-31 09:30:00")));
+        assert_eq!(parse_cutoff("2026-01-31 09:30", now), Ok(local("2026-01-31 09:30:00")));
+    }
+
+    #[test]
+    fn cutoffs_reject_bad_input() {
+        for value in ["", "d", "30", "30x", "-2d", "1.5d", "30 days", "2026-13-01", "2026-02-30", "31/01/2026", "yesterday"] {
+            let error = parse_cutoff(value, 0).unwrap_err();
+            assert!(error.starts_with(&format!("Invalid --modified-since '{}'", value)), "{}", error);
+        }
+    }
+
+    /// A file entry of the structure
+    fn file(path: &str, size: u64) -> FileEntry {
+        FileEntry {
+            entry_type: "file".to_string(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            size: Some(size),
+            ..Default::default()
+        }
+    }
+
+    /// A directory entry of the structure
+    fn dir(path: &str, children: Vec<FileEntry>) -> FileEntry {
+        FileEntry {
+            entry_type: "directory".to_string(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            children: Some(children),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the modification time of a file to `seconds` after the Unix epoch
+    fn set_mtime(path: &Path, seconds: u64) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(seconds)).unwrap();
+    }
+
+    #[test]
+    fn stale_files_use_commit_times_and_fall_back_to_mtime() {
+        let repo = tempfile::tempdir().unwrap();
+        let git = |args: &[&str], date: &str| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(repo.path())
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com", "-c", "commit.gpgsign=false"])
+                .args(args)
+                .env("GIT_AUTHOR_DATE", date)
+                .env("GIT_COMMITTER_DATE", date)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"], "");
+        fs::write(repo.path().join("old.rs"), "old").unwrap();
+        fs::write(repo.path().join("touched.rs"), "touched").unwrap();
+        git(&["add", "."], "");
+        git(&["commit", "-q", "-m", "old"], "2020-01-01T00:00:00Z");
+        fs::write(repo.path().join("touched.rs"), "touched again").unwrap();
+        git(&["commit", "-q", "-am", "new"], "2026-01-01T00:00:00Z");
+        // A fresh mtime on a committed file doesn't count, the commit does
+        set_mtime(&repo.path().join("old.rs"), 1_900_000_000);
+        // Untracked files have no commit and use their mtime
+        fs::write(repo.path().join("untracked-old.txt"), "").unwrap();
+        set_mtime(&repo.path().join("untracked-old.txt"), 1_500_000_000);
+        fs::write(repo.path().join("untracked-new.txt"), "").unwrap();
+        set_mtime(&repo.path().join("untracked-new.txt"), 1_800_000_000);
+
+        let times = commit_times(repo.path());
+        assert_eq!(times.get("old.rs"), Some(&1_577_836_800));
+        assert_eq!(times.get("touched.rs"), Some(&1_767_225_600));
+        assert_eq!(times.get("untracked-old.txt"), None);
+
+        let mut structure = vec![
+            file("old.rs", 3),
+            file("touched.rs", 13),
+            file("untracked-new.txt", 0),
+            file("untracked-old.txt", 0),
+            file("vanished.txt", 0),
+        ];
+        let cutoff = 1_700_000_000;
+        assert_eq!(mark_stale(&mut structure, repo.path(), &times, cutoff), 2);
+        let stale: Vec<_> = structure.iter().filter(|entry| entry.stale == Some(true)).map(|entry| entry.path.as_str()).collect();
+        assert_eq!(stale, ["old.rs", "untracked-old.txt"]);
+        assert!(structure.iter().take(4).all(|entry| entry.modified.is_some()));
+        // Files without any time are kept
+        assert_eq!((structure[4].modified.as_ref(), structure[4].stale), (None, None));
+    }
+
+    #[test]
+    fn stale_files_outside_git_use_mtime() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("src")).unwrap();
+        fs::write(root.path().join("src/old.rs"), "").unwrap();
+        set_mtime(&root.path().join("src/old.rs"), 1_500_000_000);
+        fs::write(root.path().join("src/new.rs"), "").unwrap();
+
+        let times = commit_times(root.path());
+        assert!(times.is_empty());
+        let mut structure = vec![dir("src", vec![file("src/new.rs", 0), file("src/old.rs", 0)])];
+        assert_eq!(mark_stale(&mut structure, root.path(), &times, 1_700_000_000), 1);
+        let children = structure[0].children.as_ref().unwrap();
+        assert_eq!((children[0].stale, children[1].stale), (None, Some(true)));
+    }
+
+    #[test]
+    fn line_endings_convert_crlf_and_mixed_text() {
+        let mixed = "one\r\ntwo\nthree\rfour\r\n";
+        assert_eq!(LineEndings::Lf.apply(mixed).as_deref(), Some("one\ntwo\nthree\rfour\n"));
+        assert_eq!(LineEndings::Crlf.apply(mixed).as_deref(), Some("one\r\ntwo\r\nthree\rfour\r\n"));
+        assert_eq!(LineEndings::Keep.apply(mixed), None);
+        // Nothing to convert
+        assert_eq!(LineEndings::Lf.apply("one\ntwo\n"), None);
+        assert_eq!(LineEndings::Crlf.apply("one\r\ntwo"), None);
+        assert_eq!(LineEndings::Lf.apply("classic\rmac\r"), None);
+    }
+
+    /// A processor configured from command line arguments (the repository path comes first)
+    fn processor(args: &[&str]) -> FileProcessor {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        FileProcessor::new(&Config::from_args(&args).unwrap())
+    }
+
+    #[test]
+    fn markdown_escapes_special_characters() {
+        let cases = [
+            ("src/main.rs", "src/main.rs"),
+            ("`code`.md", "\\`code\\`.md"),
+            ("#1 notes.md", "\\#1 notes.md"),
+            ("[draft]*final*.txt", "\\[draft\\]\\*final\\*.txt"),
+            ("snake_case_name.py", "snake\\_case\\_name.py"),
+            ("a|b~c<d>&e.txt", "a\\|b\\~c\\<d\\>\\&e.txt"),
+            ("back\\slash", "back\\\\slash"),
+            ("docs/日本語 ファイル.md", "docs/日本語 ファイル.md"),
+        ];
+        for (path, escaped) in cases {
+            assert_eq!(escape_markdown(path), escaped, "{}", path);
+        }
+    }
+
+    #[test]
+    fn slugs_keep_letters_and_digits_of_any_script() {
+        let cases = [
+            ("src/main.rs", "src-main-rs"),
+            ("README.md", "readme-md"),
+            ("`weird` [name] #1.md", "weird-name-1-md"),
+            ("docs/Ünïcödé.md", "docs-ünïcödé-md"),
+            ("docs/日本語.md", "docs-日本語-md"),
+            ("__init__.py", "init-py"),
+            ("---", ""),
+        ];
+        for (path, slug) in cases {
+            assert_eq!(slugify(path), slug, "{}", path);
+        }
+    }
+
+    #[test]
+    fn duplicate_slugs_get_numbered_anchors() {
+        let processor = processor(&["repo"]);
+        // All three paths slugify to `a-b-rs`
+        let structure = vec![
+            dir("a", vec![file("a/b.rs", 1)]),
+            file("a-b.rs", 1),
+            file("a_b.rs", 1),
+            file("c.rs", 1),
+        ];
+        let mut anchors = HashMap::new();
+        processor.file_anchors(&structure, &mut HashSet::new(), &mut anchors);
+        assert_eq!(anchors["a/b.rs"], "file-a-b-rs");
+        assert_eq!(anchors["a-b.rs"], "file-a-b-rs-2");
+        assert_eq!(anchors["a_b.rs"], "file-a-b-rs-3");
+        assert_eq!(anchors["c.rs"], "file-c-rs");
+        assert_eq!(anchors.len(), 4);
+    }
+
+    #[test]
+    fn anchors_include_the_path_prefix() {
+        let processor = processor(&["repo", "--path-prefix", "My Repo"]);
+        let mut anchors = HashMap::new();
+        processor.file_anchors(&[file("src/lib.rs", 1)], &mut HashSet::new(), &mut anchors);
+        assert_eq!(anchors["src/lib.rs"], "file-my-repo-src-lib-rs");
+    }
+
+    #[test]
+    fn output_stems_number_taken_timestamps() {
+        let taken = |names: &'static [&'static str]| move |stem: &str| names.contains(&stem);
+        assert_eq!(output_stem("repo", None, taken(&["repo"])), "repo");
+        assert_eq!(output_stem("repo", Some("20240511T143210"), taken(&[])), "repo-20240511T143210");
+        assert_eq!(output_stem("repo", Some("20240511T143210"), taken(&["repo-20240511T143210"])), "repo-20240511T143210-1");
+        assert_eq!(
+            output_stem("repo", Some("20240511T143210"), taken(&["repo-20240511T143210", "repo-20240511T143210-1"])),
+            "repo-20240511T143210-2"
+        );
+        // Numbering fills the first gap
+        assert_eq!(
+            output_stem("repo", Some("20240511T143210"), taken(&["repo-20240511T143210", "repo-20240511T143210-2"])),
+            "repo-20240511T143210-1"
+        );
+        // Other timestamps don't count
+        assert_eq!(output_stem("repo", Some("20240511T143211"), taken(&["repo-20240511T143210"])), "repo-20240511T143211");
+    }
+
+    #[test]
+    fn priority_tiers_follow_names_and_directories() {
+        use PriorityTier::*;
+        let cases = [
+            ("README.md", Readme),
+            ("readme.rst", Readme),
+            ("docs/README.md", Docs),
+            ("CHANGELOG.md", Docs),
+            ("docs/guide/setup.txt", Docs),
+            ("doc/api.rs", Docs),
+            ("Cargo.toml", Manifest),
+            ("crates/core/Cargo.toml", Manifest),
+            ("web/package.json", Manifest),
+            ("requirements-dev.txt", Manifest),
+            ("config.yaml", Manifest),
+            ("src/config.yaml", Other),
+            ("src/main.rs", Source),
+            ("scripts/deploy.sh", Source),
+            ("lib/Parser.JAVA", Source),
+            ("tests/cli.rs", Tests),
+            ("src/__tests__/app.tsx", Tests),
+            ("pkg/parser_test.go", Tests),
+            ("test_utils.py", Tests),
+            ("src/app.spec.ts", Tests),
+            ("src/button.test.jsx", Tests),
+            ("spec/fixtures/data.json", Tests),
+            ("src/attest.rs", Source),
+            ("src/contest_results.py", Source),
+            ("assets/logo.png", Other),
+            ("LICENSE", Other),
+            ("src/notes.md", Other),
+        ];
+        for (path, tier) in cases {
+            assert_eq!(priority_tier(path), tier, "{}", path);
+        }
+        assert!(Readme < Docs && Docs < Manifest && Manifest < Source && Source < Tests && Tests < Other);
+    }
+
+    #[test]
+    fn roll_up_totals_nested_directories() {
+        let with_lines = |mut entry: FileEntry, lines: usize| {
+            entry.lines = Some(lines);
+            entry
+        };
+        let mut stale = file("src/old.rs", 1000);
+        stale.stale = Some(true);
+        let mut truncated = dir("vendor", Vec::new());
+        truncated.truncated = Some(true);
+        truncated.file_count = Some(7);
+        truncated.size = Some(700);
+        let mut submodule = dir("lib/external", vec![file("lib/external/big.rs", 5000)]);
+        submodule.entry_type = "submodule".to_string();
+
+        let mut structure = vec![
+            dir(
+                "src",
+                vec![
+                    dir("src/core", vec![with_lines(file("src/core/a.rs", 10), 1), with_lines(file("src/core/b.rs", 20), 2)]),
+                    dir("src/empty", Vec::new()),
+                    with_lines(file("src/main.rs", 30), 3),
+                    stale,
+                ],
+            ),
+            dir("lib", vec![submodule]),
+            truncated,
+            file("README.md", 5),
+        ];
+        // README.md has no line count (contents not read), so only the directories below have one
+        assert_eq!(roll_up(&mut structure), (11, 765, Some(6)));
+
+        let src = &structure[0];
+        assert_eq!((src.file_count, src.size, src.lines), (Some(3), Some(60), Some(6)));
+        let core = &src.children.as_ref().unwrap()[0];
+        assert_eq!((core.file_count, core.size, core.lines), (Some(2), Some(30), Some(3)));
+        let empty = &src.children.as_ref().unwrap()[1];
+        assert_eq!((empty.file_count, empty.size, empty.lines), (Some(0), Some(0), None));
+        // Submodules don't count, truncated directories keep what was recorded at the depth limit
+        assert_eq!((structure[1].file_count, structure[1].size), (Some(0), Some(0)));
+        assert_eq!((structure[2].file_count, structure[2].size), (Some(7), Some(700)));
+    }
+
+    #[test]
+    fn minified_bundles_are_told_apart_from_long_lines_in_source() {
+        let bundle = include_bytes!("../tests/fixtures/bundle.js");
+        let long_url = include_bytes!("../tests/fixtures/long_url.rs");
+        assert_eq!(classify_generated("bundle.js", bundle), Some("minified"));
+        // One 6 KB URL among ordinary lines of code
+        assert!(String::from_utf8_lossy(long_url).lines().any(|line| line.len() > MINIFIED_LINE_LENGTH));
+        assert_eq!(classify_generated("long_url.rs", long_url), None);
+        // Names and header markers decide without looking at line lengths
+        assert_eq!(classify_generated("app.min.js", b"var a=1;\n"), Some("minified"));
+        assert_eq!(classify_generated("schema.rs", b"// @generated by protoc\nstruct A;\n"), Some("generated"));
+        assert_eq!(classify_generated("api.go", b"// Code generated by oapi. DO NOT EDIT.\npackage api\n"), Some("generated"));
+        // Markers below the header are just text
+        let mentioned = format!("{}Files marked @generated are skipped\n", "line\n".repeat(10));
+        assert_eq!(classify_generated("notes.md", mentioned.as_bytes()), None);
+    }
+
+    #[test]
+    fn sizes_parse_units_and_reject_overflow() {
+        assert_eq!(parse_size("2048"), Ok(2048));
+        assert_eq!(parse_size("16k"), Ok(16 * 1024));
+        assert_eq!(parse_size("1MB"), Ok(1024 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("many"), Err("Invalid size 'many'".to_string()));
+        assert_eq!(parse_size("99999999999999G"), Err("Size '99999999999999G' is too large".to_string()));
+    }
+
+    #[test]
+    fn previews_keep_both_ends() {
+        let text = "1\n2\n3\n4\n5\n";
+        assert_eq!(preview_lines(text, 1).as_deref(), Some("1\n... (3 lines omitted) ...\n5\n"));
+        assert_eq!(preview_lines(text, 2).as_deref(), Some("1\n2\n... (1 lines omitted) ...\n4\n5\n"));
+        assert_eq!(preview_lines(text, 3), None);
+        assert_eq!(preview_lines(text, usize::MAX), None);
+    }
+
+    #[test]
+    fn since_refs_cannot_pass_git_options() {
+        let args = |since: &str| vec![".".to_string(), "--since".to_string(), since.to_string()];
+        let error = Config::from_args(&args("--output=/tmp/injected")).err().unwrap();
+        assert_eq!(error, "Invalid --since '--output=/tmp/injected' (expected a git ref)");
+        assert!(Config::from_args(&args("-p")).is_err());
+        assert_eq!(Config::from_args(&args("HEAD~2")).unwrap().since.as_deref(), Some("HEAD~2"));
+    }
+
+    #[test]
+    fn depth_counts_from_the_selected_path() {
+        let whole = processor(&[".", "--max-depth", "1"]);
+        assert_eq!(whole.walk_depth("sub"), Some(1));
+        assert_eq!(whole.walk_depth("sub/deep/er"), Some(3));
+
+        let selected = processor(&[".", "--path", "sub/inner", "--path", "other", "--max-depth", "1"]);
+        assert_eq!(selected.walk_depth("sub/inner"), Some(0));
+        assert_eq!(selected.walk_depth("sub/inner/deep"), Some(1));
+        assert_eq!(selected.walk_depth("other/a/b"), Some(2));
+        // Directories on the way to a --path are never cut off
+        assert_eq!(selected.walk_depth("sub"), None);
+    }
+
+    #[test]
+    fn threaded_walks_are_deterministic() {
+        let root = TempDir::new().unwrap();
+        for top in 0..10 {
+            let dir = root.path().join(format!("d{}/a/b/c", top));
+            fs::create_dir_all(&dir).unwrap();
+            for file in ["x.rs", "y.rs"] {
+                fs::write(dir.join(file), "fn x() {}\n").unwrap();
+                fs::write(root.path().join(format!("d{}", top)).join(file), "fn x() {}\n").unwrap();
+            }
+        }
+        let walk = || {
+            let processor = processor(&[&root.path().to_string_lossy()]);
+            // Walk on other threads even on a single core
+            processor.walk_threads.store(8, Ordering::Release);
+            let structure = processor.get_file_structure(root.path(), Path::new(""), &[]).unwrap();
+            serde_json::to_string(&structure).unwrap()
+        };
+        let first = walk();
+        assert!(first.contains("d9/a/b/c/y.rs"));
+        for _ in 0..5 {
+            assert_eq!(walk(), first);
+        }
+    }
 }
@@ -0,0 +1,120 @@
+//! Built-in ignore profiles (--preset).
+//! A preset is a bundle of exclusion patterns (gitignore syntax) and limits. Its patterns have the lowest
+//! precedence, so pattern files and --include/--exclude can re-include or drop anything it decides.
+
+/// A named bundle of exclusion patterns and limits
+#[derive(Debug)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub patterns: &'static [&'static [&'static str]], // Groups of gitignore-style patterns, applied in order
+    pub max_lines: Option<usize>,                     // Omit files with more lines (unless --max-lines is given)
+}
+
+/// Documentation, changelogs and licenses
+const DOCS: &[&str] = &[
+    "*.md", "*.markdown", "*.rst", "*.adoc", "*.txt", "!CMakeLists.txt", "!requirements*.txt", "/docs/", "/doc/",
+    "LICENSE*", "LICENCE*", "COPYING*", "CHANGELOG*", "AUTHORS*",
+];
+
+/// Images and other media
+const IMAGES: &[&str] = &["*.png", "*.jpg", "*.jpeg", "*.gif", "*.svg", "*.ico", "*.webp", "*.bmp", "*.avif"];
+
+/// Dependency lock files
+pub const LOCKFILES: &[&str] = &[
+    "Cargo.lock", "package-lock.json", "yarn.lock", "pnpm-lock.yaml", "bun.lockb", "poetry.lock", "Pipfile.lock",
+    "Gemfile.lock", "composer.lock", "go.sum", "*.lock",
+];
+
+/// Continuous integration configuration
+const CI: &[&str] = &[
+    "/.github/", "/.gitlab-ci.yml", "/.gitlab/", "/.circleci/", "/.travis.yml", "/azure-pipelines.yml",
+    "/.buildkite/", "/Jenkinsfile",
+];
+
+/// Test fixtures and snapshots
+const FIXTURES: &[&str] = &["fixtures/", "__fixtures__/", "testdata/", "__snapshots__/", "*.snap"];
+
+/// Tests, examples and benchmarks
+const TESTS: &[&str] = &[
+    "tests/", "test/", "__tests__/", "spec/", "examples/", "example/", "benches/", "*_test.*", "*.test.*",
+    "*.spec.*", "test_*.py",
+];
+
+/// Everything except documentation and the manifests at the root
+const DOCS_ONLY: &[&str] = &[
+    "*", "!*/", "!*.md", "!*.markdown", "!*.rst", "!*.adoc", "!/Cargo.toml", "!/package.json", "!/pyproject.toml",
+    "!/go.mod", "!/pom.xml", "!/build.gradle", "!/build.gradle.kts", "!/Gemfile", "!/composer.json",
+];
+
+/// All built-in presets
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "code-only",
+        description: "drop docs, markdown, images, lockfiles, CI configuration and test fixtures",
+        patterns: &[DOCS, IMAGES, LOCKFILES, CI, FIXTURES],
+        max_lines: None,
+    },
+    Preset {
+        name: "docs-only",
+        description: "keep only markdown, reStructuredText and AsciiDoc files plus the manifests at the root",
+        patterns: &[DOCS_ONLY],
+        max_lines: None,
+    },
+    Preset {
+        name: "minimal",
+        description: "code-only, also dropping tests and examples, and omitting files over 500 lines",
+        patterns: &[DOCS, IMAGES, LOCKFILES, CI, FIXTURES, TESTS],
+        max_lines: Some(500),
+    },
+];
+
+impl Preset {
+    /// Looks up a preset by name
+    pub fn find(name: &str) -> Result<&'static Self, String> {
+        PRESETS.iter().find(|preset| preset.name == name).ok_or_else(|| {
+            let names: Vec<_> = PRESETS.iter().map(|preset| preset.name).collect();
+            format!("Unknown preset '{}' (expected {})", name, names.join(", "))
+        })
+    }
+
+    /// All patterns of the preset, in order
+    pub fn patterns(&self) -> impl Iterator<Item = &'static str> {
+        self.patterns.iter().flat_map(|group| group.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_found_by_name() {
+        for preset in PRESETS {
+            assert_eq!(Preset::find(preset.name).unwrap().name, preset.name);
+        }
+        let error = Preset::find("tiny").unwrap_err();
+        assert_eq!(error, "Unknown preset 'tiny' (expected code-only, docs-only, minimal)");
+    }
+
+    #[test]
+    fn patterns_are_flattened_in_group_order() {
+        let minimal: Vec<_> = Preset::find("minimal").unwrap().patterns().collect();
+        let code_only: Vec<_> = Preset::find("code-only").unwrap().patterns().collect();
+        // minimal is code-only plus the test groups at the end
+        assert_eq!(&minimal[..code_only.len()], &code_only[..]);
+        assert_eq!(&minimal[code_only.len()..], TESTS);
+        assert_eq!(minimal.first(), Some(&"*.md"));
+    }
+
+    #[test]
+    fn patterns_are_valid_globs() {
+        for preset in PRESETS {
+            let mut builder = ignore::gitignore::GitignoreBuilder::new("");
+            for pattern in preset.patterns() {
+                builder.add_line(None, pattern).unwrap_or_else(|e| panic!("{}: {}: {}", preset.name, pattern, e));
+            }
+            builder.build().unwrap();
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Run report: every file that was skipped, shortened or could not be read, with the reason.
+//! Summarized at the end of a run, optionally saved as JSON (--report) and checked by --strict.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use super::ConcatError;
+
+/// Why a file did not make it into the output unchanged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    Unreadable,    // Reading the file failed (e.g. permission denied)
+    BrokenSymlink, // Symbolic link pointing to a missing target
+    OutputLimit,   // Omitted after reaching --max-output-size
+    Binary,        // Binary content skipped
+    NonUtf8,       // Invalid UTF-8 sequences replaced with U+FFFD
+    FilterFailed,  // --filter-cmd failed, content included unfiltered
+    Generated,     // Detected as generated or minified, content skipped
+    TooManyLines,  // Over --max-lines or a --limit line count, content omitted
+    TooLarge,      // Over a --limit size, content omitted
+    Previewed,     // Over twice --preview-lines, shortened to its first and last lines
+}
+
+impl Reason {
+    /// All reasons, in summary order
+    pub const ALL: [Self; 10] = [
+        Self::Unreadable,
+        Self::BrokenSymlink,
+        Self::OutputLimit,
+        Self::Binary,
+        Self::NonUtf8,
+        Self::FilterFailed,
+        Self::Generated,
+        Self::TooManyLines,
+        Self::TooLarge,
+        Self::Previewed,
+    ];
+
+    /// Name used on the command line and in the JSON report
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Unreadable => "unreadable",
+            Self::BrokenSymlink => "broken_symlink",
+            Self::OutputLimit => "output_limit",
+            Self::Binary => "binary",
+            Self::NonUtf8 => "non_utf8",
+            Self::FilterFailed => "filter_failed",
+            Self::Generated => "generated",
+            Self::TooManyLines => "too_many_lines",
+            Self::TooLarge => "too_large",
+            Self::Previewed => "previewed",
+        }
+    }
+
+    /// Parses a comma-separated list of reason names, e.g. `unreadable,non_utf8`
+    pub fn parse_list(list: &str) -> Result<Vec<Self>, String> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                Self::ALL.into_iter().find(|reason| reason.name() == name).ok_or_else(|| {
+                    let names: Vec<_> = Self::ALL.iter().map(Self::name).collect();
+                    format!("Unknown report category '{}' (expected {})", name, names.join(", "))
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the file's contents are left out of the document
+    pub fn skips_content(&self) -> bool {
+        !matches!(self, Self::NonUtf8 | Self::FilterFailed | Self::Previewed)
+    }
+
+    /// Whether this is worth a warning; generated and over-long files are skipped on request
+    pub fn is_warning(&self) -> bool {
+        !matches!(self, Self::Generated | Self::TooManyLines | Self::TooLarge | Self::Previewed)
+    }
+
+    /// Summary phrase for `count` files, e.g. "3 files unreadable"
+    fn describe(&self, count: usize) -> String {
+        let files = if count == 1 { "file" } else { "files" };
+        match self {
+            Self::Unreadable => format!("{} {} unreadable", count, files),
+            Self::BrokenSymlink => format!("{} broken {} skipped", count, if count == 1 { "symlink" } else { "symlinks" }),
+            Self::OutputLimit => format!("{} {} omitted at the output size limit", count, files),
+            Self::Binary => format!("{} binary {} skipped", count, files),
+            Self::NonUtf8 => format!("{} {} with invalid UTF-8 (replaced with U+FFFD)", count, files),
+            Self::FilterFailed => format!("{} {} left unfiltered (--filter-cmd failed)", count, files),
+            Self::Generated => format!("{} generated/minified {} skipped", count, files),
+            Self::TooManyLines => format!("{} {} over a line limit omitted", count, files),
+            Self::TooLarge => format!("{} {} over a --limit size omitted", count, files),
+            Self::Previewed => format!("{} {} shortened to a --preview-lines preview", count, files),
+        }
+    }
+}
+
+/// One reported file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: String,   // Relative path of the file
+    pub reason: Reason, // What happened to it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>, // Error message, size or line count
+}
+
+/// All events of a run
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub counts: BTreeMap<Reason, usize>, // Number of entries per reason
+    pub entries: Vec<Entry>,             // Every event, in processing order
+}
+
+impl Report {
+    /// Records an event for a file
+    pub fn add(&mut self, path: &str, reason: Reason, detail: Option<String>) {
+        *self.counts.entry(reason).or_default() += 1;
+        self.entries.push(Entry { path: path.to_string(), reason, detail });
+    }
+
+    /// Number of events with the given reason
+    pub fn count(&self, reason: Reason) -> usize {
+        self.counts.get(&reason).copied().unwrap_or(0)
+    }
+
+    /// Grouped one-line summary of the reasons selected by `filter`, e.g.
+    /// "12 binary files skipped, 3 files unreadable" (None when there is nothing to report)
+    pub fn summary(&self, filter: impl Fn(Reason) -> bool) -> Option<String> {
+        let parts: Vec<String> = Reason::ALL
+            .into_iter()
+            .filter(|&reason| filter(reason) && self.count(reason) > 0)
+            .map(|reason| reason.describe(self.count(reason)))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    /// Writes the report as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> Result<(), ConcatError> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json + "\n").map_err(|source| ConcatError::OutputWrite { path: path.to_path_buf(), source })
+    }
+}
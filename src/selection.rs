@@ -0,0 +1,212 @@
+//! File selection layer between the structure scan and content processing.
+//! Supports an interactive numbered checklist and saving/loading selections as JSON.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+
+use super::{ConcatError, FileEntry};
+
+/// A saved set of selected files
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Selection {
+    pub files: Vec<String>, // Relative paths of the selected files
+}
+
+impl Selection {
+    /// Loads a selection previously written with `--save-selection`
+    pub fn load(path: &Path) -> Result<Self, ConcatError> {
+        let content = fs::read_to_string(path).map_err(|e| ConcatError::io(path, e))?;
+        serde_json::from_str(&content).map_err(|e| {
+            ConcatError::InvalidArguments(format!("Invalid selection file '{}': {}", path.display(), e))
+        })
+    }
+
+    /// Writes the selection as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> Result<(), ConcatError> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json + "\n").map_err(|source| ConcatError::OutputWrite { path: path.to_path_buf(), source })
+    }
+
+    /// Builds a selection containing every file in the structure
+    pub fn from_structure(entries: &[FileEntry]) -> Self {
+        let mut files = Vec::new();
+        for_each_file(entries, &mut |entry| files.push(entry.path.clone()));
+        Self { files }
+    }
+
+    /// Removes unselected files from the structure, dropping directories left empty
+    pub fn apply(&self, entries: Vec<FileEntry>) -> Vec<FileEntry> {
+        let selected: HashSet<&str> = self.files.iter().map(String::as_str).collect();
+        prune(entries, &selected)
+    }
+}
+
+/// Calls `f` for every file in the structure, depth first
+fn for_each_file<'a>(entries: &'a [FileEntry], f: &mut impl FnMut(&'a FileEntry)) {
+    for entry in entries {
+        match &entry.children {
+            Some(children) => for_each_file(children, f),
+            None => f(entry),
+        }
+    }
+}
+
+/// Keeps only selected files and the directories containing them
+fn prune(entries: Vec<FileEntry>, selected: &HashSet<&str>) -> Vec<FileEntry> {
+    entries
+        .into_iter()
+        .filter_map(|mut entry| match entry.children.take() {
+            Some(children) => {
+                let children = prune(children, selected);
+                (!children.is_empty()).then_some(FileEntry { children: Some(children), ..entry })
+            }
+            None => selected.contains(entry.path.as_str()).then_some(entry),
+        })
+        .collect()
+}
+
+/// A row of the interactive checklist
+struct Row<'a> {
+    entry: &'a FileEntry,  // File or directory shown in this row
+    depth: usize,          // Nesting level used for indentation
+    files: Vec<usize>,     // Indices (into the file list) of the files this row covers
+}
+
+/// Presents the structure as a numbered checklist on the terminal and returns the chosen files.
+/// Starts from `initial` (everything selected when None).
+pub fn interactive(entries: &[FileEntry], initial: Option<&Selection>) -> Result<Selection, ConcatError> {
+    // Flatten the tree into rows, remembering which files each directory covers
+    let mut files: Vec<&FileEntry> = Vec::new();
+    let mut rows = Vec::new();
+    flatten(entries, 0, &mut files, &mut rows);
+
+    let mut selected: Vec<bool> = match initial {
+        Some(selection) => {
+            let chosen: HashSet<&str> = selection.files.iter().map(String::as_str).collect();
+            files.iter().map(|file| chosen.contains(file.path.as_str())).collect()
+        }
+        None => vec![true; files.len()],
+    };
+    let mut filter = String::new();
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    loop {
+        // Render the (filtered) checklist with the running total
+        let mut stderr = io::stderr();
+        let _ = writeln!(stderr);
+        for (index, row) in rows.iter().enumerate() {
+            if !filter.is_empty() && !fuzzy_match(&filter, &row.entry.path) {
+                continue;
+            }
+            let chosen = row.files.iter().filter(|&&f| selected[f]).count();
+            let mark = match chosen {
+                0 => ' ',
+                n if n == row.files.len() => 'x',
+                _ => '~',
+            };
+            let suffix = if row.entry.children.is_some() { "/" } else { "" };
+            let _ = writeln!(
+                stderr,
+                "{:>4} [{}] {}{}{}",
+                index + 1,
+                mark,
+                "  ".repeat(row.depth),
+                row.entry.name,
+                suffix
+            );
+        }
+        let (count, bytes) = files
+            .iter()
+            .zip(&selected)
+            .filter(|(_, &chosen)| chosen)
+            .fold((0, 0), |(count, bytes), (file, _)| (count + 1, bytes + file.size.unwrap_or(0)));
+        let _ = writeln!(stderr, "\nSelected: {} of {} files, {} bytes", count, files.len(), bytes);
+        let _ = write!(
+            stderr,
+            "Toggle <n>, <n-m>; /<filter> (empty to clear); a = all, n = none; Enter = done, q = quit: "
+        );
+        let _ = stderr.flush();
+
+        let mut line = String::new();
+        if input.read_line(&mut line).map_err(|e| ConcatError::io(Path::new("<stdin>"), e))? == 0 {
+            break;
+        }
+        let command = line.trim();
+        match command {
+            "" => break,
+            "q" => return Err(ConcatError::InvalidArguments("Interactive selection cancelled".to_string())),
+            "a" => selected.iter_mut().for_each(|chosen| *chosen = true),
+            "n" => selected.iter_mut().for_each(|chosen| *chosen = false),
+            _ if command.starts_with('/') => filter = command[1..].to_string(),
+            _ => {
+                for token in command.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+                    match parse_range(token, rows.len()) {
+                        Some((start, end)) => {
+                            for row in &rows[start..=end] {
+                                // A directory toggles its whole subtree: all on unless already all on
+                                let all_on = row.files.iter().all(|&f| selected[f]);
+                                for &f in &row.files {
+                                    selected[f] = !all_on;
+                                }
+                            }
+                        }
+                        None => eprintln!("Invalid entry '{}'", token),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Selection {
+        files: files
+            .iter()
+            .zip(&selected)
+            .filter(|(_, &chosen)| chosen)
+            .map(|(file, _)| file.path.clone())
+            .collect(),
+    })
+}
+
+/// Flattens the tree into checklist rows; returns the file indices covered by `entries`
+fn flatten<'a>(entries: &'a [FileEntry], depth: usize, files: &mut Vec<&'a FileEntry>, rows: &mut Vec<Row<'a>>) -> Vec<usize> {
+    let mut covered = Vec::new();
+    for entry in entries {
+        let row_index = rows.len();
+        rows.push(Row { entry, depth, files: Vec::new() });
+        let row_files = match &entry.children {
+            Some(children) => flatten(children, depth + 1, files, rows),
+            None => {
+                files.push(entry);
+                vec![files.len() - 1]
+            }
+        };
+        covered.extend(&row_files);
+        rows[row_index].files = row_files;
+    }
+    covered
+}
+
+/// Parses a 1-based row number or range (`3`, `2-5`) into 0-based inclusive bounds
+fn parse_range(token: &str, len: usize) -> Option<(usize, usize)> {
+    let (start, end) = match token.split_once('-') {
+        Some((start, end)) => (start.parse::<usize>().ok()?, end.parse::<usize>().ok()?),
+        None => {
+            let n = token.parse::<usize>().ok()?;
+            (n, n)
+        }
+    };
+    (start >= 1 && start <= end && end <= len).then(|| (start - 1, end - 1))
+}
+
+/// Case-insensitive subsequence match, so `smr` matches `src/main.rs`
+fn fuzzy_match(pattern: &str, text: &str) -> bool {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|p| chars.any(|c| c == p))
+}
@@ -0,0 +1,90 @@
+//! Sidecar manifest describing a generated document (--manifest).
+//! Lists every emitted file with the byte range of its section, so downstream tools can slice the document
+//! without parsing it, together with the files whose contents were left out and why.
+
+use std::fs;
+use std::path::Path;
+use serde::Serialize;
+
+use super::ConcatError;
+use super::report::Reason;
+
+/// Sidecar format version, bumped on incompatible changes
+const FORMAT_VERSION: u32 = 1;
+
+/// Description of a generated document
+#[derive(Debug, Serialize)]
+pub struct Sidecar {
+    pub format_version: u32,
+    pub tool_version: &'static str,
+    pub repository: String,     // Repository name, as used for the output file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>, // HEAD commit SHA, if the source is a git repository
+    pub total_files: usize,     // Number of file sections in the document
+    pub files: Vec<SidecarFile>, // File sections, in document order
+    pub skipped: Vec<Skipped>,  // Files whose contents were left out
+}
+
+/// One file section of the document
+#[derive(Debug, Serialize)]
+pub struct SidecarFile {
+    pub path: String,           // Relative path of the file
+    pub language: String,       // Language of the code fence
+    pub size: u64,              // File size in bytes
+    pub lines: usize,           // Lines of text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>, // Hash of the file as stored on disk (None if unreadable)
+    pub offset: usize,          // Byte offset of the section in the (uncompressed) document
+    pub length: usize,          // Section length in bytes
+}
+
+/// A file whose contents are not in the document
+#[derive(Debug, Serialize)]
+pub struct Skipped {
+    pub path: String,
+    pub reason: Reason,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>, // Error message, size or line count
+}
+
+impl Sidecar {
+    /// Describes a document with the given file sections (sorted into document order here)
+    pub fn new(repository: String, commit: Option<String>, mut files: Vec<SidecarFile>, skipped: Vec<Skipped>) -> Self {
+        files.sort_by_key(|file| file.offset);
+        Self {
+            format_version: FORMAT_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION"),
+            repository,
+            commit,
+            total_files: files.len(),
+            files,
+            skipped,
+        }
+    }
+
+    /// Writes the manifest as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> Result<(), ConcatError> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json + "\n").map_err(|source| ConcatError::OutputWrite { path: path.to_path_buf(), source })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, offset: usize, length: usize) -> SidecarFile {
+        SidecarFile { path: path.to_string(), language: "rust".to_string(), size: 1, lines: 1, sha256: None, offset, length }
+    }
+
+    #[test]
+    fn files_are_listed_in_document_order() {
+        let sidecar = Sidecar::new("repo".to_string(), None, vec![file("b.rs", 40, 10), file("a.rs", 10, 30)], Vec::new());
+        let paths: Vec<_> = sidecar.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, ["a.rs", "b.rs"]);
+        assert_eq!(sidecar.total_files, 2);
+        let json = serde_json::to_value(&sidecar).unwrap();
+        assert!(json.get("commit").is_none());
+        assert!(json["files"][0].get("sha256").is_none());
+    }
+}
@@ -0,0 +1,31 @@
+//! Headings of awkward paths are escaped and every table of contents link has exactly one target
+
+mod common;
+
+use common::Fixture;
+
+#[test]
+fn toc_links_resolve_to_unique_anchors() {
+    let fixture = Fixture::git_repo(&[
+        ("a/b.rs", "fn nested() {}\n"),
+        ("a-b.rs", "fn flat() {}\n"),
+        ("[draft]*v2*.md", "draft\n"),
+        ("`tick`#1.txt", "tick\n"),
+        ("docs/日本語.md", "text\n"),
+    ]);
+    fixture.run_ok(&["--toc"]);
+    let document = fixture.document();
+
+    for heading in ["## \\[draft\\]\\*v2\\*.md", "## \\`tick\\`\\#1.txt", "## docs/日本語.md", "## a/b.rs", "## a-b.rs"] {
+        assert!(document.lines().any(|line| line == heading), "missing heading {}", heading);
+    }
+
+    let links: Vec<&str> = document.lines().filter_map(|line| line.split_once("](#")).map(|(_, rest)| rest.trim_end_matches(')')).collect();
+    assert_eq!(links.len(), 5, "{}", document);
+    assert!(links.contains(&"file-a-b-rs") && links.contains(&"file-a-b-rs-2"), "{:?}", links);
+    assert!(links.contains(&"file-docs-日本語-md"), "{:?}", links);
+    for link in links {
+        let target = format!("<a id=\"{}\"></a>", link);
+        assert_eq!(document.matches(&target).count(), 1, "anchor {}", link);
+    }
+}
@@ -0,0 +1,78 @@
+//! `--token` reaches git only through the askpass helper: never on a command line, in the cached clone's
+//! remote URL or in the document
+
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use assert_cmd::Command;
+use common::{stderr, Fixture};
+
+const TOKEN: &str = "ghp_askpassTestToken0123456789";
+
+/// Puts a `git` wrapper into `bin` that logs its arguments and the askpass helper's answers, then runs the real git
+fn wrap_git(bin: &Path) {
+    let real = std::process::Command::new("sh").args(["-c", "command -v git"]).output().unwrap();
+    let real = String::from_utf8(real.stdout).unwrap();
+    let log = bin.parent().unwrap();
+    let script = format!(
+        "#!/bin/sh\n\
+         printf '%s\\n' \"$*\" >> '{log}/argv.log'\n\
+         if [ -n \"$GIT_ASKPASS\" ]; then\n\
+         \x20 printf '%s|%s|%s\\n' \"$GIT_TERMINAL_PROMPT\" \"$(\"$GIT_ASKPASS\" 'Username for x')\" \"$(\"$GIT_ASKPASS\" 'Password for x')\" >> '{log}/askpass.log'\n\
+         fi\n\
+         exec '{real}' \"$@\"\n",
+        log = log.display(),
+        real = real.trim()
+    );
+    fs::create_dir_all(bin).unwrap();
+    let wrapper = bin.join("git");
+    fs::write(&wrapper, script).unwrap();
+    fs::set_permissions(&wrapper, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn tokens_only_reach_git_through_askpass() {
+    let fixture = Fixture::git_repo(&[("src/lib.rs", "pub fn lib() {}\n")]);
+    let bin = fixture.root().join("logs/bin");
+    wrap_git(&bin);
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+    let remote = format!("file://{}", fixture.path().display());
+    let cache = fixture.root().join("cache");
+
+    let output = Command::cargo_bin("repo-to-ai-context-docs")
+        .unwrap()
+        .current_dir(fixture.root())
+        .env("PATH", path)
+        .env_remove("GIT_CONCAT_TOKEN")
+        .env_remove("GITHUB_TOKEN")
+        .args([remote.as_str(), "--remote", "--token", TOKEN, "--cache-dir"])
+        .arg(&cache)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stderr(&output).contains(TOKEN));
+
+    let argv = fs::read_to_string(fixture.root().join("logs/argv.log")).unwrap();
+    assert!(argv.contains("clone"), "{}", argv);
+    assert!(!argv.contains(TOKEN), "{}", argv);
+    assert!(argv.contains("credential.helper="), "{}", argv);
+
+    // Every remote git command got the helper, and the helper answers with the token
+    let askpass = fs::read_to_string(fixture.root().join("logs/askpass.log")).unwrap();
+    assert!(!askpass.is_empty());
+    for line in askpass.lines() {
+        assert_eq!(line, format!("0|x-access-token|{}", TOKEN));
+    }
+
+    let config = fs::read_dir(&cache).unwrap().filter_map(|entry| fs::read_to_string(entry.unwrap().path().join(".git/config")).ok()).collect::<String>();
+    assert!(config.contains("[remote \"origin\"]"), "{}", config);
+    assert!(!config.contains(TOKEN), "{}", config);
+    let document = fs::read_dir(fixture.root().join("output")).unwrap().map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap()).collect::<String>();
+    assert!(document.contains("pub fn lib()"));
+    assert!(!document.contains(TOKEN));
+}
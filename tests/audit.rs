@@ -0,0 +1,42 @@
+//! --audit names the .env* files the dotfile policy leaves out, without counting them as findings
+
+mod common;
+
+use common::{stderr, Fixture};
+
+fn fixture() -> Fixture {
+    Fixture::git_repo(&[
+        ("src/main.rs", "fn main() {}\n"),
+        (".env", "DB_PASSWORD=\"correct-horse-battery\"\n"),
+        ("api/.env.local", "TOKEN=abc\n"),
+    ])
+}
+
+#[test]
+fn excluded_env_files_are_listed_but_pass() {
+    let fixture = fixture();
+    let output = fixture.run_ok(&["--fail-on-audit"]);
+    let stderr = stderr(&output);
+    assert!(stderr.contains("Audit: no risky files found"), "{}", stderr);
+    assert!(stderr.contains("Left out, not scanned (--include-env would include them): .env, api/.env.local"), "{}", stderr);
+    assert!(!fixture.document().contains("correct-horse-battery"));
+}
+
+#[test]
+fn included_env_files_are_findings() {
+    let fixture = fixture();
+    let output = fixture.run(&["--fail-on-audit", "--include-env"]);
+    let stderr = stderr(&output);
+    assert_eq!(output.status.code(), Some(9), "{}", stderr);
+    assert!(stderr.contains("Audit: 3 findings"), "{}", stderr);
+    assert!(stderr.contains("env_file  .env  (environment file)"), "{}", stderr);
+    assert!(stderr.contains("secret    .env  (hard-coded credential on line 1)"), "{}", stderr);
+    assert!(stderr.contains("env_file  api/.env.local  (environment file)"), "{}", stderr);
+    assert!(!stderr.contains("Left out"), "{}", stderr);
+}
+
+#[test]
+fn env_files_are_only_listed_when_auditing() {
+    let output = fixture().run_ok(&[]);
+    assert!(!stderr(&output).contains(".env"), "{}", stderr(&output));
+}
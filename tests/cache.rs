@@ -0,0 +1,115 @@
+//! The clone cache (--cache-dir): updates, --refresh-cache, broken entries and offline runs, with a local bare
+//! repository standing in for the remote
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Output;
+
+use assert_cmd::Command;
+use common::{stderr, Fixture};
+
+/// A working repository and a bare copy of it that runs clone through `file://`
+struct Remote {
+    fixture: Fixture,
+}
+
+impl Remote {
+    fn new() -> Self {
+        let fixture = Fixture::git_repo(&[("src/lib.rs", "pub fn version() -> u32 { 1 }\n")]);
+        let bare = fixture.root().join("origin");
+        fixture.git(&["clone", "-q", "--bare", ".", bare.to_str().unwrap()]);
+        Self { fixture }
+    }
+
+    fn bare(&self) -> PathBuf {
+        self.fixture.root().join("origin")
+    }
+
+    /// Commits a change and pushes it to the bare repository
+    fn push(&self, contents: &str) {
+        self.fixture.write("src/lib.rs", contents);
+        self.fixture.commit("update");
+        self.fixture.git(&["push", "-q", self.bare().to_str().unwrap(), "HEAD"]);
+    }
+
+    /// The cache entry of the remote
+    fn entry(&self) -> PathBuf {
+        let cache = self.fixture.root().join("cache");
+        fs::read_dir(&cache).unwrap().map(|entry| entry.unwrap().path()).find(|path| path.is_dir()).expect("cache entry")
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        let url = format!("file://{}", self.bare().display());
+        Command::cargo_bin("repo-to-ai-context-docs")
+            .unwrap()
+            .current_dir(self.fixture.root())
+            .env_remove("RUST_LOG")
+            .args([url.as_str(), "--remote", "--cache-dir", "cache"])
+            .args(args)
+            .output()
+            .unwrap()
+    }
+
+    /// Runs successfully and returns the document
+    fn document(&self, args: &[&str]) -> (String, String) {
+        let output = self.run(args);
+        assert!(output.status.success(), "{}", stderr(&output));
+        (String::from_utf8(self.fixture.output("origin.md")).unwrap(), stderr(&output))
+    }
+}
+
+#[test]
+fn cached_clones_are_updated() {
+    let remote = Remote::new();
+    assert!(remote.document(&[]).0.contains("{ 1 }"));
+    remote.push("pub fn version() -> u32 { 2 }\n");
+    // A stray file in the cached checkout is cleaned away
+    fs::write(remote.entry().join("stray.rs"), "fn stray() {}\n").unwrap();
+    let (document, _) = remote.document(&[]);
+    assert!(document.contains("{ 2 }") && !document.contains("stray"), "{}", document);
+}
+
+#[test]
+fn refreshing_replaces_the_cached_clone() {
+    let remote = Remote::new();
+    remote.document(&[]);
+    let marker = remote.entry().join(".git/marker");
+    fs::write(&marker, "").unwrap();
+    remote.document(&[]);
+    assert!(marker.exists());
+    remote.document(&["--refresh-cache"]);
+    assert!(!marker.exists());
+}
+
+#[test]
+fn broken_entries_are_cloned_again() {
+    let remote = Remote::new();
+    remote.document(&[]);
+    let entry = remote.entry();
+    fs::remove_dir_all(entry.join(".git")).unwrap();
+    fs::write(entry.join("leftover.rs"), "fn leftover() {}\n").unwrap();
+    let (document, _) = remote.document(&[]);
+    assert!(document.contains("{ 1 }") && !document.contains("leftover"), "{}", document);
+    assert!(entry.join(".git").is_dir());
+}
+
+#[test]
+fn offline_runs_use_the_last_fetched_state() {
+    let remote = Remote::new();
+    remote.push("pub fn version() -> u32 { 2 }\n");
+    remote.document(&[]);
+    // The remote disappears, as it would without network access
+    let away = remote.fixture.root().join("away");
+    fs::rename(remote.bare(), &away).unwrap();
+    let (document, warnings) = remote.document(&[]);
+    assert!(warnings.contains("Using cached clone without updating it"), "{}", warnings);
+    assert!(document.contains("{ 2 }"));
+
+    // Without a cached clone to fall back on, the run fails and leaves no entry behind
+    fs::remove_dir_all(remote.entry()).unwrap();
+    let output = remote.run(&[]);
+    assert_eq!(output.status.code(), Some(4), "{}", stderr(&output));
+    assert!(fs::read_dir(remote.fixture.root().join("cache")).unwrap().all(|entry| !entry.unwrap().path().is_dir()));
+}
@@ -0,0 +1,39 @@
+//! Names that differ only in case are kept apart and warned about, within one directory only
+
+mod common;
+
+use common::{stderr, Fixture};
+
+// Needs a case-sensitive file system to create both spellings
+#[cfg(target_os = "linux")]
+#[test]
+fn names_differing_in_case_within_a_directory_warn() {
+    let fixture = Fixture::git_repo(&[
+        ("README.md", "upper\n"),
+        ("readme.md", "lower\n"),
+        ("src/Util.rs", "// upper\n"),
+        ("src/util.rs", "// lower\n"),
+        ("Docs/a.md", "a\n"),
+        ("docs/b.md", "b\n"),
+    ]);
+    let output = fixture.run_ok(&["--toc"]);
+    let stderr = stderr(&output);
+    assert!(stderr.contains("README.md and readme.md differ only in case"), "{}", stderr);
+    assert!(stderr.contains("src/Util.rs and src/util.rs differ only in case"), "{}", stderr);
+    assert!(stderr.contains("Docs and docs differ only in case"), "{}", stderr);
+
+    // Both spellings keep their own section and anchor
+    let document = fixture.document();
+    for (heading, contents) in [("## README.md", "upper"), ("## readme.md", "lower"), ("## src/Util.rs", "// upper"), ("## src/util.rs", "// lower")] {
+        assert!(document.lines().any(|line| line == heading), "missing {}", heading);
+        assert!(document.contains(contents));
+    }
+    assert!(document.contains("<a id=\"file-readme-md\"></a>") && document.contains("<a id=\"file-readme-md-2\"></a>"));
+}
+
+#[test]
+fn same_names_in_different_directories_dont_warn() {
+    let fixture = Fixture::git_repo(&[("a/Config.rs", "a\n"), ("b/config.rs", "b\n"), ("src/mod.rs", "\n"), ("tests/MOD.rs", "\n")]);
+    let output = fixture.run_ok(&[]);
+    assert!(!stderr(&output).contains("differ only in case"), "{}", stderr(&output));
+}
@@ -0,0 +1,115 @@
+//! Shared helpers for the integration tests: a scratch repository and runs of the binary against it
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+/// A scratch directory holding a repository named `repo`; runs write their output to `output/` next to it
+pub struct Fixture {
+    root: TempDir,
+}
+
+impl Fixture {
+    /// Creates an empty repository directory
+    pub fn new() -> Self {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("repo")).unwrap();
+        Self { root }
+    }
+
+    /// Creates a git repository with everything written by `files` committed
+    pub fn git_repo(files: &[(&str, &str)]) -> Self {
+        let fixture = Self::new();
+        fixture.git(&["init", "-q"]);
+        for (path, contents) in files {
+            fixture.write(path, contents);
+        }
+        fixture.commit("initial");
+        fixture
+    }
+
+    /// Path of the repository
+    pub fn path(&self) -> PathBuf {
+        self.root.path().join("repo")
+    }
+
+    /// Path of the scratch directory (the working directory of runs)
+    pub fn root(&self) -> &Path {
+        self.root.path()
+    }
+
+    /// Writes a file of the repository, creating its parent directories
+    pub fn write(&self, path: &str, contents: impl AsRef<[u8]>) {
+        let path = self.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// Runs git in the repository
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(self.path())
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com", "-c", "commit.gpgsign=false"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    /// Commits all changes
+    pub fn commit(&self, message: &str) {
+        self.git(&["add", "-A"]);
+        self.git(&["commit", "-q", "--allow-empty", "-m", message]);
+    }
+
+    /// A command running the binary on the repository from the scratch directory
+    pub fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::cargo_bin("repo-to-ai-context-docs").unwrap();
+        command.current_dir(self.root()).env_remove("RUST_LOG").arg(self.path()).args(args);
+        command
+    }
+
+    /// Runs the binary on the repository
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.command(args).output().unwrap()
+    }
+
+    /// Runs the binary and asserts that it succeeded
+    pub fn run_ok(&self, args: &[&str]) -> Output {
+        let output = self.run(args);
+        assert!(output.status.success(), "run {:?} failed: {}", args, stderr(&output));
+        output
+    }
+
+    /// Path of an output file, e.g. `repo.md`
+    pub fn output_path(&self, name: &str) -> PathBuf {
+        self.root().join("output").join(name)
+    }
+
+    /// Contents of an output file
+    pub fn output(&self, name: &str) -> Vec<u8> {
+        fs::read(self.output_path(name)).unwrap_or_else(|e| panic!("output/{}: {}", name, e))
+    }
+
+    /// Contents of the markdown document
+    pub fn document(&self) -> String {
+        String::from_utf8(self.output("repo.md")).unwrap()
+    }
+}
+
+/// Standard error of a run, as text
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Standard output of a run, as text
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
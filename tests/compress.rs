@@ -0,0 +1,93 @@
+//! --compress: decompressing the output yields exactly the uncompressed document
+
+mod common;
+
+use std::fs;
+use std::io::Read;
+
+use common::{stderr, stdout, Fixture};
+
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture.write("README.md", "# Demo\n\nSome text.\n");
+    fixture.write("src/main.rs", "fn main() {\n    println!(\"hello\");\n}\n".repeat(50));
+    fixture.write("src/lib.rs", "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n");
+    fixture.write("data/values.csv", (0..500).map(|n| format!("{},{}\n", n, n * n)).collect::<String>());
+    fixture.write("image.bin", [0u8, 159, 146, 150, 255, 0, 1]);
+    fixture
+}
+
+fn gunzip(bytes: &[u8]) -> Vec<u8> {
+    let mut text = Vec::new();
+    flate2::read::MultiGzDecoder::new(bytes).read_to_end(&mut text).unwrap();
+    text
+}
+
+/// Files left in the output directory
+fn output_files(fixture: &Fixture) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(fixture.root().join("output"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn gzip_and_zstd_round_trip_byte_for_byte() {
+    for format in [&["--stats", "--toc"][..], &["--format", "text", "--stats"][..], &["--format", "jsonl"][..]] {
+        let fixture = fixture();
+        let extension = match format {
+            ["--format", "text", ..] => "txt",
+            ["--format", "jsonl"] => "jsonl",
+            _ => "md",
+        };
+        fixture.run_ok(format);
+        let plain = fixture.output(&format!("repo.{}", extension));
+
+        let mut args = format.to_vec();
+        args.extend(["--compress", "gzip"]);
+        let output = fixture.run_ok(&args);
+        let compressed = fixture.output(&format!("repo.{}.gz", extension));
+        assert_eq!(gunzip(&compressed), plain, "{:?}", format);
+        assert!(compressed.len() < plain.len());
+        let message = format!("({} bytes compressed, {} bytes uncompressed)", compressed.len(), plain.len());
+        assert!(stdout(&output).contains(&message), "{}", stdout(&output));
+
+        let mut args = format.to_vec();
+        args.extend(["--compress", "zstd"]);
+        fixture.run_ok(&args);
+        let compressed = fixture.output(&format!("repo.{}.zst", extension));
+        assert_eq!(zstd::decode_all(&compressed[..]).unwrap(), plain, "{:?}", format);
+
+        // No temporary files stay behind
+        let expected: Vec<String> = ["", ".gz", ".zst"].iter().map(|suffix| format!("repo.{}{}", extension, suffix)).collect();
+        assert_eq!(output_files(&fixture), expected);
+    }
+}
+
+#[test]
+fn manifest_offsets_refer_to_the_uncompressed_document() {
+    let fixture = fixture();
+    let manifest = fixture.root().join("manifest.json");
+    fixture.run_ok(&["--compress", "gzip", "--manifest", manifest.to_str().unwrap()]);
+    let document = gunzip(&fixture.output("repo.md.gz"));
+    let manifest: serde_json::Value = serde_json::from_slice(&fs::read(&manifest).unwrap()).unwrap();
+
+    for file in manifest["files"].as_array().unwrap() {
+        let offset = file["offset"].as_u64().unwrap() as usize;
+        let length = file["length"].as_u64().unwrap() as usize;
+        let section = String::from_utf8_lossy(&document[offset..offset + length]).into_owned();
+        let path = file["path"].as_str().unwrap();
+        assert!(section.starts_with("<a id=\"file-"), "{}: {:?}", path, section);
+        assert!(section.contains(&format!("## {}", path.replace('_', "\\_"))), "{}: {:?}", path, section);
+    }
+}
+
+#[test]
+fn failed_runs_leave_no_partial_output() {
+    let fixture = fixture();
+    let output = fixture.run(&["--compress", "gzip", "--max-output-size", "1K"]);
+    assert_eq!(output.status.code(), Some(7), "{}", stderr(&output));
+    assert!(output_files(&fixture).is_empty());
+}
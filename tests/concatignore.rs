@@ -0,0 +1,48 @@
+//! Nested .concatignore files are scoped to their directory, and `!` patterns re-include files inside
+//! directories excluded by the same or an outer file
+
+mod common;
+
+use common::Fixture;
+
+/// Headings of the file sections in the document
+fn sections(fixture: &Fixture) -> Vec<String> {
+    fixture.document().lines().filter_map(|line| line.strip_prefix("## ")).map(str::to_string).collect()
+}
+
+#[test]
+fn nested_files_re_include_under_excluded_directories() {
+    let fixture = Fixture::new();
+    fixture.write(".concatignore", "fixtures/\n!fixtures/schema.json\n");
+    fixture.write("fixtures/schema.json", "{}\n");
+    fixture.write("fixtures/data.json", "[]\n");
+    fixture.write("pkg/.concatignore", "generated/\n!generated/keep.rs\n*.log\n");
+    fixture.write("pkg/generated/keep.rs", "fn keep() {}\n");
+    fixture.write("pkg/generated/other.rs", "fn other() {}\n");
+    fixture.write("pkg/generated/deeper/more.rs", "fn more() {}\n");
+    fixture.write("pkg/debug.log", "log\n");
+    fixture.write("pkg/sub/deep.log", "log\n");
+    fixture.write("pkg/lib.rs", "fn lib() {}\n");
+    // The nested rules don't reach outside their directory
+    fixture.write("debug.log", "log\n");
+    fixture.write("other/generated/kept.rs", "fn kept() {}\n");
+    fixture.run_ok(&[]);
+
+    assert_eq!(
+        sections(&fixture),
+        ["debug.log", "fixtures/schema.json", "other/generated/kept.rs", "pkg/generated/keep.rs", "pkg/lib.rs"]
+    );
+    assert!(!fixture.document().contains(".concatignore"));
+}
+
+#[test]
+fn nested_rules_take_precedence_over_outer_ones() {
+    let fixture = Fixture::new();
+    fixture.write(".concatignore", "*.json\n");
+    fixture.write("keep/.concatignore", "!config.json\n");
+    fixture.write("keep/config.json", "{}\n");
+    fixture.write("keep/data.json", "[]\n");
+    fixture.write("top.json", "{}\n");
+    fixture.run_ok(&[]);
+    assert_eq!(sections(&fixture), ["keep/config.json"]);
+}
@@ -0,0 +1,24 @@
+//! --dedup collapses identical files and reports the savings in the run summary
+
+mod common;
+
+use common::{stdout, Fixture};
+
+#[test]
+fn collapsed_duplicates_are_summarized() {
+    let fixture = Fixture::new();
+    fixture.write("a/LICENSE", "MIT License\n");
+    fixture.write("b/LICENSE", "MIT License\n");
+    fixture.write("c/LICENSE", "MIT License\n");
+    fixture.write("main.rs", "fn main() {}\n");
+    let summary = stdout(&fixture.run_ok(&["--dedup"]));
+    assert!(summary.contains("Collapsed 2 duplicate files (24 bytes saved)"), "{}", summary);
+    assert!(fixture.document().contains("## c/LICENSE — identical to a/LICENSE"));
+
+    // Nothing to report without duplicates, and nothing at all with --quiet
+    fixture.write("b/LICENSE", "Apache License\n");
+    fixture.write("c/LICENSE", "BSD License\n");
+    assert!(!stdout(&fixture.run_ok(&["--dedup"])).contains("Collapsed"));
+    fixture.write("c/LICENSE", "MIT License\n");
+    assert!(!stdout(&fixture.run_ok(&["--dedup", "--quiet"])).contains("Collapsed"));
+}
@@ -0,0 +1,92 @@
+//! The dotfile policy: --hidden / --include-hidden, --no-hidden and --include-env, alone and combined
+
+mod common;
+
+use common::Fixture;
+use serde_json::Value;
+
+/// A repository with dotfiles of every kind the policy tells apart
+fn fixture() -> Fixture {
+    Fixture::git_repo(&[
+        ("src/main.rs", "fn main() {}\n"),
+        ("src/.hidden.rs", "// hidden\n"),
+        (".gitignore", "*.tmp\n"),
+        (".github/workflows/ci.yml", "on: push\n"),
+        (".config/tool.toml", "key = 1\n"),
+        (".idea/workspace.xml", "<project/>\n"),
+        (".vscode/settings.json", "{}\n"),
+        (".env", "KEY=value\n"),
+        (".env.example", "KEY=\n"),
+        (".DS_Store", "finder\n"),
+    ])
+}
+
+/// Paths of the files in the structure generated with `args`, sorted
+fn files(fixture: &Fixture, args: &[&str]) -> Vec<String> {
+    fn collect(entries: &Value, paths: &mut Vec<String>) {
+        for entry in entries.as_array().unwrap() {
+            match entry.get("children") {
+                Some(children) => collect(children, paths),
+                None => paths.push(entry["path"].as_str().unwrap().to_string()),
+            }
+        }
+    }
+    let mut args = args.to_vec();
+    args.extend(["--structure-only", "--format", "json"]);
+    fixture.run_ok(&args);
+    let mut paths = Vec::new();
+    collect(&serde_json::from_slice(&fixture.output("repo.json")).unwrap(), &mut paths);
+    paths.sort();
+    paths
+}
+
+#[test]
+fn dotfile_policy_matrix() {
+    let fixture = fixture();
+    let default = [".config/tool.toml", ".github/workflows/ci.yml", ".gitignore", "src/.hidden.rs", "src/main.rs"];
+    let hidden = [
+        ".config/tool.toml",
+        ".github/workflows/ci.yml",
+        ".gitignore",
+        ".idea/workspace.xml",
+        ".vscode/settings.json",
+        "src/.hidden.rs",
+        "src/main.rs",
+    ];
+    let env = [".env", ".env.example"];
+    let with_env = |base: &[&'static str]| {
+        let mut paths: Vec<&'static str> = base.iter().chain(&env).copied().collect();
+        paths.sort();
+        paths
+    };
+
+    let cases: [(&[&str], Vec<&str>); 9] = [
+        (&[], default.to_vec()),
+        (&["--hidden"], hidden.to_vec()),
+        (&["--include-hidden"], hidden.to_vec()),
+        (&["--no-hidden"], vec!["src/main.rs"]),
+        (&["--include-env"], with_env(&default)),
+        (&["--hidden", "--include-env"], with_env(&hidden)),
+        // --no-hidden leaves out .env files too
+        (&["--no-hidden", "--include-env"], vec!["src/main.rs"]),
+        // The last of two conflicting flags wins
+        (&["--hidden", "--no-hidden"], vec!["src/main.rs"]),
+        (&["--no-hidden", "--include-hidden"], hidden.to_vec()),
+    ];
+    for (args, expected) in cases {
+        assert_eq!(files(&fixture, args), expected, "{:?}", args);
+    }
+}
+
+#[test]
+fn ignore_sets_apply_under_every_policy() {
+    let fixture = fixture();
+    for args in [&[][..], &["--hidden"], &["--hidden", "--include-env"]] {
+        let paths = files(&fixture, args);
+        assert!(!paths.iter().any(|path| path.starts_with(".git/") || path == ".DS_Store"), "{:?}: {:?}", args, paths);
+    }
+    // Patterns still exclude dotfiles that the policy lets through
+    let paths = files(&fixture, &["--hidden", "--exclude", ".idea/"]);
+    assert!(!paths.iter().any(|path| path.starts_with(".idea/")), "{:?}", paths);
+    assert!(paths.iter().any(|path| path.starts_with(".vscode/")), "{:?}", paths);
+}
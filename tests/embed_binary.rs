@@ -0,0 +1,70 @@
+//! Binary files embedded with --embed-binary decode back to their exact bytes in every output format
+
+mod common;
+
+use base64::Engine;
+use common::{stderr, Fixture};
+use sha2::{Digest, Sha256};
+
+/// Every byte value, twice, then a tail that needs base64 padding
+fn payload() -> Vec<u8> {
+    let mut bytes: Vec<u8> = (0..=255).chain(0..=255).collect();
+    bytes.extend_from_slice(&[0, 0xff, 0x80, 0x0d, 0x0a]);
+    bytes
+}
+
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture.write("assets/blob.bin", payload());
+    fixture.write("assets/large.bin", vec![0u8; 8 * 1024]);
+    fixture
+}
+
+/// Decodes the base64 lines following `sha256: <hash>` in a markdown or text section, checking the hash line
+fn decode_section(document: &str, heading: &str) -> Vec<u8> {
+    let section = &document[document.find(heading).unwrap_or_else(|| panic!("no {}", heading))..];
+    let (header, rest) = section.split_once("\n\n").unwrap().1.split_once("\n\n").unwrap();
+    assert!(header.starts_with(&format!("Base64-encoded binary file ({} bytes)", payload().len())), "{}", header);
+    let hash = header.split_once("sha256: ").unwrap().1;
+    assert_eq!(hash, hex(&Sha256::digest(payload())));
+
+    let lines: Vec<&str> = rest.lines().skip_while(|line| line.starts_with("```")).take_while(|line| !line.is_empty() && !line.starts_with("```")).collect();
+    assert!(lines.iter().all(|line| line.len() <= 76), "{:?}", lines);
+    base64::engine::general_purpose::STANDARD.decode(lines.concat()).unwrap()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn markdown_and_text_blocks_round_trip() {
+    let fixture = fixture();
+    fixture.run_ok(&["--embed-binary", "4K"]);
+    let document = fixture.document();
+    assert_eq!(decode_section(&document, "## assets/blob.bin\n"), payload());
+    assert!(document.contains("```base64\n"));
+    // Files over the limit are still skipped
+    assert!(document.contains("## assets/large.bin\n\n```\n[Binary or non-UTF8 file content skipped]"), "{}", document);
+
+    fixture.run_ok(&["--embed-binary", "4K", "--format", "text"]);
+    let text = String::from_utf8(fixture.output("repo.txt")).unwrap();
+    assert_eq!(decode_section(&text, "===== assets/blob.bin =====\n"), payload());
+    assert!(!text.contains("```"));
+}
+
+#[test]
+fn jsonl_records_round_trip() {
+    let fixture = fixture();
+    let output = fixture.run_ok(&["--embed-binary", "4K", "--format", "jsonl"]);
+    let jsonl = String::from_utf8(fixture.output("repo.jsonl")).unwrap();
+    let record: serde_json::Value = jsonl
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .find(|record| record["path"] == "assets/blob.bin")
+        .unwrap_or_else(|| panic!("no record: {}", stderr(&output)));
+    assert_eq!(record["encoding"], "base64");
+    assert_eq!(record["sha256"], hex(&Sha256::digest(payload())));
+    let decoded = base64::engine::general_purpose::STANDARD.decode(record["content"].as_str().unwrap()).unwrap();
+    assert_eq!(decoded, payload());
+}
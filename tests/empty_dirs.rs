@@ -0,0 +1,59 @@
+//! --keep-empty-dirs keeps directories without included files, but never excluded ones
+
+mod common;
+
+use std::fs;
+
+use common::Fixture;
+use serde_json::Value;
+
+/// A plain directory tree (git doesn't track empty directories)
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture.write("src/main.rs", "fn main() {}\n");
+    fixture.write("images/logo.png", "not really a png");
+    fixture.write("node_modules/dep/index.js", "module.exports = 1;\n");
+    fixture.write("generated/out.rs", "// out\n");
+    for dir in ["empty", "nested/deeper"] {
+        fs::create_dir_all(fixture.path().join(dir)).unwrap();
+    }
+    fixture
+}
+
+/// Paths of all directories in a structure, depth first
+fn directories(entries: &Value, paths: &mut Vec<String>) {
+    for entry in entries.as_array().unwrap() {
+        if entry["type"] == "directory" {
+            paths.push(entry["path"].as_str().unwrap().to_string());
+            directories(&entry["children"], paths);
+        }
+    }
+}
+
+/// Directories of the structure generated with `args`
+fn structure(fixture: &Fixture, args: &[&str]) -> Vec<String> {
+    let mut args = args.to_vec();
+    args.extend(["--structure-only", "--format", "json", "--exclude", "generated/"]);
+    fixture.run_ok(&args);
+    let structure: Value = serde_json::from_slice(&fixture.output("repo.json")).unwrap();
+    let mut paths = Vec::new();
+    directories(&structure, &mut paths);
+    paths
+}
+
+#[test]
+fn empty_directories_are_dropped_by_default() {
+    assert_eq!(structure(&fixture(), &[]), ["src"]);
+}
+
+#[test]
+fn keep_empty_dirs_keeps_empty_and_filtered_directories() {
+    let fixture = fixture();
+    // images/ only holds a file with a binary extension
+    assert_eq!(structure(&fixture, &["--keep-empty-dirs"]), ["empty", "images", "nested", "nested/deeper", "src"]);
+
+    fixture.run_ok(&["--keep-empty-dirs", "--exclude", "generated/"]);
+    let document = fixture.document();
+    assert!(document.contains("\"path\": \"nested/deeper\",\n") && document.contains("\"file_count\": 0"), "{}", document);
+    assert!(!document.contains("node_modules") && !document.contains("generated"));
+}
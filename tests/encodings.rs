@@ -0,0 +1,74 @@
+//! Text in other encodings is transcoded to UTF-8 and labeled; damaged UTF-8 stays UTF-8
+
+mod common;
+
+use common::{stderr, Fixture};
+
+/// A repository holding every file of tests/fixtures/encodings
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    for name in ["utf16le.txt", "utf16be.txt", "shift_jis.txt", "latin1.txt", "broken_utf8.txt"] {
+        fixture.write(name, std::fs::read(format!("{}/tests/fixtures/encodings/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap());
+    }
+    fixture
+}
+
+/// Body of the section of `heading`, up to the next section
+fn section<'a>(document: &'a str, heading: &str) -> &'a str {
+    let start = document.find(heading).unwrap_or_else(|| panic!("no heading {}", heading)) + heading.len();
+    let rest = &document[start..];
+    &rest[..rest.find("<a id=").unwrap_or(rest.len())]
+}
+
+#[test]
+fn legacy_encodings_are_transcoded_and_labeled() {
+    let fixture = fixture();
+    fixture.run_ok(&[]);
+    let document = fixture.document();
+
+    let utf16 = "Grüße aus München — naïve café\nZweite Zeile\n";
+    assert!(section(&document, "## utf16le.txt (transcoded from UTF-16LE)\n").contains(utf16));
+    assert!(section(&document, "## utf16be.txt (transcoded from UTF-16BE)\n").contains(utf16));
+    assert!(section(&document, "## shift\\_jis.txt (transcoded from Shift_JIS)\n").contains("これは日本語のテキストです。\n文字コードはシフトJISです。"));
+    assert!(section(&document, "## latin1.txt (transcoded from windows-1252)\n").contains("Déjà vu: le garçon a mangé une crème brûlée."));
+    for encoding in ["UTF-16LE", "UTF-16BE", "Shift_JIS", "windows-1252"] {
+        assert_eq!(document.matches(&format!("\"encoding\": \"{}\"", encoding)).count(), 1, "{}", encoding);
+    }
+    // The byte order mark is not part of the text
+    assert!(!document.contains('\u{feff}'));
+}
+
+#[test]
+fn damaged_utf8_is_decoded_lossily() {
+    let fixture = fixture();
+    let output = fixture.run_ok(&["-vv"]);
+    let document = fixture.document();
+
+    // Valid characters around the damage survive instead of turning into windows-1252 mojibake
+    let body = section(&document, "## broken\\_utf8.txt\n");
+    assert!(body.contains("valid ünïcode line\nbad \u{fffd}\u{fffd} bytes \u{fffd} here\nmore ünïcode\n"), "{}", body);
+    assert!(!document.contains("Ã¼"));
+    assert!(stderr(&output).contains("broken_utf8.txt is not valid UTF-8, inserted 3 replacement character(s)"), "{}", stderr(&output));
+}
+
+#[test]
+fn lossy_files_are_reported_and_fail_strict_runs() {
+    let fixture = fixture();
+    let report = fixture.root().join("report.json");
+    let report_arg = report.to_string_lossy().into_owned();
+    let output = fixture.run_ok(&["--report", &report_arg]);
+    assert!(stderr(&output).contains("Warning: 1 file with invalid UTF-8 (replaced with U+FFFD)"), "{}", stderr(&output));
+
+    // Transcoded files are not problems, only the lossy one is
+    let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(report["counts"], serde_json::json!({ "non_utf8": 1 }));
+    assert_eq!(
+        report["entries"],
+        serde_json::json!([{ "path": "broken_utf8.txt", "reason": "non_utf8", "detail": "3 replacement character(s)" }])
+    );
+
+    let output = fixture.run(&["--strict-on", "non_utf8"]);
+    assert_eq!(output.status.code(), Some(8), "{}", stderr(&output));
+    fixture.write("broken_utf8.txt", "repaired ünïcode\n");
+    fixture.run_ok(&["--strict-on", "non_utf8"]);
+}
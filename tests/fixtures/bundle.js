@@ -0,0 +1,2 @@
+/*! app v1.0.0 */
+!function(e){var t={};function n(r){if(t[r])return t[r].exports;var o=t[r]={i:r,l:!1,exports:{}};return e[r].call(o.exports,o,o.exports,n),o.l=!0,o.exports}n(0)}({0:function(e,t,n){"use strict";var r=n(0),o=n(0);t.f0=function(a,b){return r.a(a)+o.b(b)*0};t.g0=function(c){return"module-0-"+c.toString(36)}},1:function(e,t,n){"use strict";var r=n(7),o=n(13);t.f1=function(a,b){return r.a(a)+o.b(b)*1};t.g1=function(c){return"module-1-"+c.toString(36)}},2:function(e,t,n){"use strict";var r=n(14),o=n(26);t.f2=function(a,b){return r.a(a)+o.b(b)*2};t.g2=function(c){return"module-2-"+c.toString(36)}},3:function(e,t,n){"use strict";var r=n(21),o=n(39);t.f3=function(a,b){return r.a(a)+o.b(b)*3};t.g3=function(c){return"module-3-"+c.toString(36)}},4:function(e,t,n){"use strict";var r=n(28),o=n(52);t.f4=function(a,b){return r.a(a)+o.b(b)*4};t.g4=function(c){return"module-4-"+c.toString(36)}},5:function(e,t,n){"use strict";var r=n(35),o=n(65);t.f5=function(a,b){return r.a(a)+o.b(b)*5};t.g5=function(c){return"module-5-"+c.toString(36)}},6:function(e,t,n){"use strict";var r=n(42),o=n(78);t.f6=function(a,b){return r.a(a)+o.b(b)*6};t.g6=function(c){return"module-6-"+c.toString(36)}},7:function(e,t,n){"use strict";var r=n(49),o=n(91);t.f7=function(a,b){return r.a(a)+o.b(b)*7};t.g7=function(c){return"module-7-"+c.toString(36)}},8:function(e,t,n){"use strict";var r=n(56),o=n(104);t.f8=function(a,b){return r.a(a)+o.b(b)*8};t.g8=function(c){return"module-8-"+c.toString(36)}},9:function(e,t,n){"use strict";var r=n(63),o=n(117);t.f9=function(a,b){return r.a(a)+o.b(b)*9};t.g9=function(c){return"module-9-"+c.toString(36)}},10:function(e,t,n){"use strict";var r=n(70),o=n(10);t.f10=function(a,b){return r.a(a)+o.b(b)*10};t.g10=function(c){return"module-10-"+c.toString(36)}},11:function(e,t,n){"use strict";var r=n(77),o=n(23);t.f11=function(a,b){return r.a(a)+o.b(b)*11};t.g11=function(c){return"module-11-"+c.toString(36)}},12:function(e,t,n){"use strict";var r=n(84),o=n(36);t.f12=function(a,b){return r.a(a)+o.b(b)*12};t.g12=function(c){return"module-12-"+c.toString(36)}},13:function(e,t,n){"use strict";var r=n(91),o=n(49);t.f13=function(a,b){return r.a(a)+o.b(b)*13};t.g13=function(c){return"module-13-"+c.toString(36)}},14:function(e,t,n){"use strict";var r=n(98),o=n(62);t.f14=function(a,b){return r.a(a)+o.b(b)*14};t.g14=function(c){return"module-14-"+c.toString(36)}},15:function(e,t,n){"use strict";var r=n(105),o=n(75);t.f15=function(a,b){return r.a(a)+o.b(b)*15};t.g15=function(c){return"module-15-"+c.toString(36)}},16:function(e,t,n){"use strict";var r=n(112),o=n(88);t.f16=function(a,b){return r.a(a)+o.b(b)*16};t.g16=function(c){return"module-16-"+c.toString(36)}},17:function(e,t,n){"use strict";var r=n(119),o=n(101);t.f17=function(a,b){return r.a(a)+o.b(b)*17};t.g17=function(c){return"module-17-"+c.toString(36)}},18:function(e,t,n){"use strict";var r=n(6),o=n(114);t.f18=function(a,b){return r.a(a)+o.b(b)*18};t.g18=function(c){return"module-18-"+c.toString(36)}},19:function(e,t,n){"use strict";var r=n(13),o=n(7);t.f19=function(a,b){return r.a(a)+o.b(b)*19};t.g19=function(c){return"module-19-"+c.toString(36)}},20:function(e,t,n){"use strict";var r=n(20),o=n(20);t.f20=function(a,b){return r.a(a)+o.b(b)*20};t.g20=function(c){return"module-20-"+c.toString(36)}},21:function(e,t,n){"use strict";var r=n(27),o=n(33);t.f21=function(a,b){return r.a(a)+o.b(b)*21};t.g21=function(c){return"module-21-"+c.toString(36)}},22:function(e,t,n){"use strict";var r=n(34),o=n(46);t.f22=function(a,b){return r.a(a)+o.b(b)*22};t.g22=function(c){return"module-22-"+c.toString(36)}},23:function(e,t,n){"use strict";var r=n(41),o=n(59);t.f23=function(a,b){return r.a(a)+o.b(b)*23};t.g23=function(c){return"module-23-"+c.toString(36)}},24:function(e,t,n){"use strict";var r=n(48),o=n(72);t.f24=function(a,b){return r.a(a)+o.b(b)*24};t.g24=function(c){return"module-24-"+c.toString(36)}},25:function(e,t,n){"use strict";var r=n(55),o=n(85);t.f25=function(a,b){return r.a(a)+o.b(b)*25};t.g25=function(c){return"module-25-"+c.toString(36)}},26:function(e,t,n){"use strict";var r=n(62),o=n(98);t.f26=function(a,b){return r.a(a)+o.b(b)*26};t.g26=function(c){return"module-26-"+c.toString(36)}},27:function(e,t,n){"use strict";var r=n(69),o=n(111);t.f27=function(a,b){return r.a(a)+o.b(b)*27};t.g27=function(c){return"module-27-"+c.toString(36)}},28:function(e,t,n){"use strict";var r=n(76),o=n(4);t.f28=function(a,b){return r.a(a)+o.b(b)*28};t.g28=function(c){return"module-28-"+c.toString(36)}},29:function(e,t,n){"use strict";var r=n(83),o=n(17);t.f29=function(a,b){return r.a(a)+o.b(b)*29};t.g29=function(c){return"module-29-"+c.toString(36)}},30:function(e,t,n){"use strict";var r=n(90),o=n(30);t.f30=function(a,b){return r.a(a)+o.b(b)*30};t.g30=function(c){return"module-30-"+c.toString(36)}},31:function(e,t,n){"use strict";var r=n(97),o=n(43);t.f31=function(a,b){return r.a(a)+o.b(b)*31};t.g31=function(c){return"module-31-"+c.toString(36)}},32:function(e,t,n){"use strict";var r=n(104),o=n(56);t.f32=function(a,b){return r.a(a)+o.b(b)*32};t.g32=function(c){return"module-32-"+c.toString(36)}},33:function(e,t,n){"use strict";var r=n(111),o=n(69);t.f33=function(a,b){return r.a(a)+o.b(b)*33};t.g33=function(c){return"module-33-"+c.toString(36)}},34:function(e,t,n){"use strict";var r=n(118),o=n(82);t.f34=function(a,b){return r.a(a)+o.b(b)*34};t.g34=function(c){return"module-34-"+c.toString(36)}},35:function(e,t,n){"use strict";var r=n(5),o=n(95);t.f35=function(a,b){return r.a(a)+o.b(b)*35};t.g35=function(c){return"module-35-"+c.toString(36)}},36:function(e,t,n){"use strict";var r=n(12),o=n(108);t.f36=function(a,b){return r.a(a)+o.b(b)*36};t.g36=function(c){return"module-36-"+c.toString(36)}},37:function(e,t,n){"use strict";var r=n(19),o=n(1);t.f37=function(a,b){return r.a(a)+o.b(b)*37};t.g37=function(c){return"module-37-"+c.toString(36)}},38:function(e,t,n){"use strict";var r=n(26),o=n(14);t.f38=function(a,b){return r.a(a)+o.b(b)*38};t.g38=function(c){return"module-38-"+c.toString(36)}},39:function(e,t,n){"use strict";var r=n(33),o=n(27);t.f39=function(a,b){return r.a(a)+o.b(b)*39};t.g39=function(c){return"module-39-"+c.toString(36)}},40:function(e,t,n){"use strict";var r=n(40),o=n(40);t.f40=function(a,b){return r.a(a)+o.b(b)*40};t.g40=function(c){return"module-40-"+c.toString(36)}},41:function(e,t,n){"use strict";var r=n(47),o=n(53);t.f41=function(a,b){return r.a(a)+o.b(b)*41};t.g41=function(c){return"module-41-"+c.toString(36)}},42:function(e,t,n){"use strict";var r=n(54),o=n(66);t.f42=function(a,b){return r.a(a)+o.b(b)*42};t.g42=function(c){return"module-42-"+c.toString(36)}},43:function(e,t,n){"use strict";var r=n(61),o=n(79);t.f43=function(a,b){return r.a(a)+o.b(b)*43};t.g43=function(c){return"module-43-"+c.toString(36)}},44:function(e,t,n){"use strict";var r=n(68),o=n(92);t.f44=function(a,b){return r.a(a)+o.b(b)*44};t.g44=function(c){return"module-44-"+c.toString(36)}},45:function(e,t,n){"use strict";var r=n(75),o=n(105);t.f45=function(a,b){return r.a(a)+o.b(b)*45};t.g45=function(c){return"module-45-"+c.toString(36)}},46:function(e,t,n){"use strict";var r=n(82),o=n(118);t.f46=function(a,b){return r.a(a)+o.b(b)*46};t.g46=function(c){return"module-46-"+c.toString(36)}},47:function(e,t,n){"use strict";var r=n(89),o=n(11);t.f47=function(a,b){return r.a(a)+o.b(b)*47};t.g47=function(c){return"module-47-"+c.toString(36)}},48:function(e,t,n){"use strict";var r=n(96),o=n(24);t.f48=function(a,b){return r.a(a)+o.b(b)*48};t.g48=function(c){return"module-48-"+c.toString(36)}},49:function(e,t,n){"use strict";var r=n(103),o=n(37);t.f49=function(a,b){return r.a(a)+o.b(b)*49};t.g49=function(c){return"module-49-"+c.toString(36)}},50:function(e,t,n){"use strict";var r=n(110),o=n(50);t.f50=function(a,b){return r.a(a)+o.b(b)*50};t.g50=function(c){return"module-50-"+c.toString(36)}},51:function(e,t,n){"use strict";var r=n(117),o=n(63);t.f51=function(a,b){return r.a(a)+o.b(b)*51};t.g51=function(c){return"module-51-"+c.toString(36)}},52:function(e,t,n){"use strict";var r=n(4),o=n(76);t.f52=function(a,b){return r.a(a)+o.b(b)*52};t.g52=function(c){return"module-52-"+c.toString(36)}},53:function(e,t,n){"use strict";var r=n(11),o=n(89);t.f53=function(a,b){return r.a(a)+o.b(b)*53};t.g53=function(c){return"module-53-"+c.toString(36)}},54:function(e,t,n){"use strict";var r=n(18),o=n(102);t.f54=function(a,b){return r.a(a)+o.b(b)*54};t.g54=function(c){return"module-54-"+c.toString(36)}},55:function(e,t,n){"use strict";var r=n(25),o=n(115);t.f55=function(a,b){return r.a(a)+o.b(b)*55};t.g55=function(c){return"module-55-"+c.toString(36)}},56:function(e,t,n){"use strict";var r=n(32),o=n(8);t.f56=function(a,b){return r.a(a)+o.b(b)*56};t.g56=function(c){return"module-56-"+c.toString(36)}},57:function(e,t,n){"use strict";var r=n(39),o=n(21);t.f57=function(a,b){return r.a(a)+o.b(b)*57};t.g57=function(c){return"module-57-"+c.toString(36)}},58:function(e,t,n){"use strict";var r=n(46),o=n(34);t.f58=function(a,b){return r.a(a)+o.b(b)*58};t.g58=function(c){return"module-58-"+c.toString(36)}},59:function(e,t,n){"use strict";var r=n(53),o=n(47);t.f59=function(a,b){return r.a(a)+o.b(b)*59};t.g59=function(c){return"module-59-"+c.toString(36)}},60:function(e,t,n){"use strict";var r=n(60),o=n(60);t.f60=function(a,b){return r.a(a)+o.b(b)*60};t.g60=function(c){return"module-60-"+c.toString(36)}},61:function(e,t,n){"use strict";var r=n(67),o=n(73);t.f61=function(a,b){return r.a(a)+o.b(b)*61};t.g61=function(c){return"module-61-"+c.toString(36)}},62:function(e,t,n){"use strict";var r=n(74),o=n(86);t.f62=function(a,b){return r.a(a)+o.b(b)*62};t.g62=function(c){return"module-62-"+c.toString(36)}},63:function(e,t,n){"use strict";var r=n(81),o=n(99);t.f63=function(a,b){return r.a(a)+o.b(b)*63};t.g63=function(c){return"module-63-"+c.toString(36)}},64:function(e,t,n){"use strict";var r=n(88),o=n(112);t.f64=function(a,b){return r.a(a)+o.b(b)*64};t.g64=function(c){return"module-64-"+c.toString(36)}},65:function(e,t,n){"use strict";var r=n(95),o=n(5);t.f65=function(a,b){return r.a(a)+o.b(b)*65};t.g65=function(c){return"module-65-"+c.toString(36)}},66:function(e,t,n){"use strict";var r=n(102),o=n(18);t.f66=function(a,b){return r.a(a)+o.b(b)*66};t.g66=function(c){return"module-66-"+c.toString(36)}},67:function(e,t,n){"use strict";var r=n(109),o=n(31);t.f67=function(a,b){return r.a(a)+o.b(b)*67};t.g67=function(c){return"module-67-"+c.toString(36)}},68:function(e,t,n){"use strict";var r=n(116),o=n(44);t.f68=function(a,b){return r.a(a)+o.b(b)*68};t.g68=function(c){return"module-68-"+c.toString(36)}},69:function(e,t,n){"use strict";var r=n(3),o=n(57);t.f69=function(a,b){return r.a(a)+o.b(b)*69};t.g69=function(c){return"module-69-"+c.toString(36)}},70:function(e,t,n){"use strict";var r=n(10),o=n(70);t.f70=function(a,b){return r.a(a)+o.b(b)*70};t.g70=function(c){return"module-70-"+c.toString(36)}},71:function(e,t,n){"use strict";var r=n(17),o=n(83);t.f71=function(a,b){return r.a(a)+o.b(b)*71};t.g71=function(c){return"module-71-"+c.toString(36)}},72:function(e,t,n){"use strict";var r=n(24),o=n(96);t.f72=function(a,b){return r.a(a)+o.b(b)*72};t.g72=function(c){return"module-72-"+c.toString(36)}},73:function(e,t,n){"use strict";var r=n(31),o=n(109);t.f73=function(a,b){return r.a(a)+o.b(b)*73};t.g73=function(c){return"module-73-"+c.toString(36)}},74:function(e,t,n){"use strict";var r=n(38),o=n(2);t.f74=function(a,b){return r.a(a)+o.b(b)*74};t.g74=function(c){return"module-74-"+c.toString(36)}},75:function(e,t,n){"use strict";var r=n(45),o=n(15);t.f75=function(a,b){return r.a(a)+o.b(b)*75};t.g75=function(c){return"module-75-"+c.toString(36)}},76:function(e,t,n){"use strict";var r=n(52),o=n(28);t.f76=function(a,b){return r.a(a)+o.b(b)*76};t.g76=function(c){return"module-76-"+c.toString(36)}},77:function(e,t,n){"use strict";var r=n(59),o=n(41);t.f77=function(a,b){return r.a(a)+o.b(b)*77};t.g77=function(c){return"module-77-"+c.toString(36)}},78:function(e,t,n){"use strict";var r=n(66),o=n(54);t.f78=function(a,b){return r.a(a)+o.b(b)*78};t.g78=function(c){return"module-78-"+c.toString(36)}},79:function(e,t,n){"use strict";var r=n(73),o=n(67);t.f79=function(a,b){return r.a(a)+o.b(b)*79};t.g79=function(c){return"module-79-"+c.toString(36)}},80:function(e,t,n){"use strict";var r=n(80),o=n(80);t.f80=function(a,b){return r.a(a)+o.b(b)*80};t.g80=function(c){return"module-80-"+c.toString(36)}},81:function(e,t,n){"use strict";var r=n(87),o=n(93);t.f81=function(a,b){return r.a(a)+o.b(b)*81};t.g81=function(c){return"module-81-"+c.toString(36)}},82:function(e,t,n){"use strict";var r=n(94),o=n(106);t.f82=function(a,b){return r.a(a)+o.b(b)*82};t.g82=function(c){return"module-82-"+c.toString(36)}},83:function(e,t,n){"use strict";var r=n(101),o=n(119);t.f83=function(a,b){return r.a(a)+o.b(b)*83};t.g83=function(c){return"module-83-"+c.toString(36)}},84:function(e,t,n){"use strict";var r=n(108),o=n(12);t.f84=function(a,b){return r.a(a)+o.b(b)*84};t.g84=function(c){return"module-84-"+c.toString(36)}},85:function(e,t,n){"use strict";var r=n(115),o=n(25);t.f85=function(a,b){return r.a(a)+o.b(b)*85};t.g85=function(c){return"module-85-"+c.toString(36)}},86:function(e,t,n){"use strict";var r=n(2),o=n(38);t.f86=function(a,b){return r.a(a)+o.b(b)*86};t.g86=function(c){return"module-86-"+c.toString(36)}},87:function(e,t,n){"use strict";var r=n(9),o=n(51);t.f87=function(a,b){return r.a(a)+o.b(b)*87};t.g87=function(c){return"module-87-"+c.toString(36)}},88:function(e,t,n){"use strict";var r=n(16),o=n(64);t.f88=function(a,b){return r.a(a)+o.b(b)*88};t.g88=function(c){return"module-88-"+c.toString(36)}},89:function(e,t,n){"use strict";var r=n(23),o=n(77);t.f89=function(a,b){return r.a(a)+o.b(b)*89};t.g89=function(c){return"module-89-"+c.toString(36)}},90:function(e,t,n){"use strict";var r=n(30),o=n(90);t.f90=function(a,b){return r.a(a)+o.b(b)*90};t.g90=function(c){return"module-90-"+c.toString(36)}},91:function(e,t,n){"use strict";var r=n(37),o=n(103);t.f91=function(a,b){return r.a(a)+o.b(b)*91};t.g91=function(c){return"module-91-"+c.toString(36)}},92:function(e,t,n){"use strict";var r=n(44),o=n(116);t.f92=function(a,b){return r.a(a)+o.b(b)*92};t.g92=function(c){return"module-92-"+c.toString(36)}},93:function(e,t,n){"use strict";var r=n(51),o=n(9);t.f93=function(a,b){return r.a(a)+o.b(b)*93};t.g93=function(c){return"module-93-"+c.toString(36)}},94:function(e,t,n){"use strict";var r=n(58),o=n(22);t.f94=function(a,b){return r.a(a)+o.b(b)*94};t.g94=function(c){return"module-94-"+c.toString(36)}},95:function(e,t,n){"use strict";var r=n(65),o=n(35);t.f95=function(a,b){return r.a(a)+o.b(b)*95};t.g95=function(c){return"module-95-"+c.toString(36)}},96:function(e,t,n){"use strict";var r=n(72),o=n(48);t.f96=function(a,b){return r.a(a)+o.b(b)*96};t.g96=function(c){return"module-96-"+c.toString(36)}},97:function(e,t,n){"use strict";var r=n(79),o=n(61);t.f97=function(a,b){return r.a(a)+o.b(b)*97};t.g97=function(c){return"module-97-"+c.toString(36)}},98:function(e,t,n){"use strict";var r=n(86),o=n(74);t.f98=function(a,b){return r.a(a)+o.b(b)*98};t.g98=function(c){return"module-98-"+c.toString(36)}},99:function(e,t,n){"use strict";var r=n(93),o=n(87);t.f99=function(a,b){return r.a(a)+o.b(b)*99};t.g99=function(c){return"module-99-"+c.toString(36)}},100:function(e,t,n){"use strict";var r=n(100),o=n(100);t.f100=function(a,b){return r.a(a)+o.b(b)*100};t.g100=function(c){return"module-100-"+c.toString(36)}},101:function(e,t,n){"use strict";var r=n(107),o=n(113);t.f101=function(a,b){return r.a(a)+o.b(b)*101};t.g101=function(c){return"module-101-"+c.toString(36)}},102:function(e,t,n){"use strict";var r=n(114),o=n(6);t.f102=function(a,b){return r.a(a)+o.b(b)*102};t.g102=function(c){return"module-102-"+c.toString(36)}},103:function(e,t,n){"use strict";var r=n(1),o=n(19);t.f103=function(a,b){return r.a(a)+o.b(b)*103};t.g103=function(c){return"module-103-"+c.toString(36)}},104:function(e,t,n){"use strict";var r=n(8),o=n(32);t.f104=function(a,b){return r.a(a)+o.b(b)*104};t.g104=function(c){return"module-104-"+c.toString(36)}},105:function(e,t,n){"use strict";var r=n(15),o=n(45);t.f105=function(a,b){return r.a(a)+o.b(b)*105};t.g105=function(c){return"module-105-"+c.toString(36)}},106:function(e,t,n){"use strict";var r=n(22),o=n(58);t.f106=function(a,b){return r.a(a)+o.b(b)*106};t.g106=function(c){return"module-106-"+c.toString(36)}},107:function(e,t,n){"use strict";var r=n(29),o=n(71);t.f107=function(a,b){return r.a(a)+o.b(b)*107};t.g107=function(c){return"module-107-"+c.toString(36)}},108:function(e,t,n){"use strict";var r=n(36),o=n(84);t.f108=function(a,b){return r.a(a)+o.b(b)*108};t.g108=function(c){return"module-108-"+c.toString(36)}},109:function(e,t,n){"use strict";var r=n(43),o=n(97);t.f109=function(a,b){return r.a(a)+o.b(b)*109};t.g109=function(c){return"module-109-"+c.toString(36)}},110:function(e,t,n){"use strict";var r=n(50),o=n(110);t.f110=function(a,b){return r.a(a)+o.b(b)*110};t.g110=function(c){return"module-110-"+c.toString(36)}},111:function(e,t,n){"use strict";var r=n(57),o=n(3);t.f111=function(a,b){return r.a(a)+o.b(b)*111};t.g111=function(c){return"module-111-"+c.toString(36)}},112:function(e,t,n){"use strict";var r=n(64),o=n(16);t.f112=function(a,b){return r.a(a)+o.b(b)*112};t.g112=function(c){return"module-112-"+c.toString(36)}},113:function(e,t,n){"use strict";var r=n(71),o=n(29);t.f113=function(a,b){return r.a(a)+o.b(b)*113};t.g113=function(c){return"module-113-"+c.toString(36)}},114:function(e,t,n){"use strict";var r=n(78),o=n(42);t.f114=function(a,b){return r.a(a)+o.b(b)*114};t.g114=function(c){return"module-114-"+c.toString(36)}},115:function(e,t,n){"use strict";var r=n(85),o=n(55);t.f115=function(a,b){return r.a(a)+o.b(b)*115};t.g115=function(c){return"module-115-"+c.toString(36)}},116:function(e,t,n){"use strict";var r=n(92),o=n(68);t.f116=function(a,b){return r.a(a)+o.b(b)*116};t.g116=function(c){return"module-116-"+c.toString(36)}},117:function(e,t,n){"use strict";var r=n(99),o=n(81);t.f117=function(a,b){return r.a(a)+o.b(b)*117};t.g117=function(c){return"module-117-"+c.toString(36)}},118:function(e,t,n){"use strict";var r=n(106),o=n(94);t.f118=function(a,b){return r.a(a)+o.b(b)*118};t.g118=function(c){return"module-118-"+c.toString(36)}},119:function(e,t,n){"use strict";var r=n(113),o=n(107);t.f119=function(a,b){return r.a(a)+o.b(b)*119};t.g119=function(c){return"module-119-"+c.toString(36)}}});
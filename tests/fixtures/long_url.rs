@@ -0,0 +1,21 @@
+//! Links to the generated asset index.
+
+use std::collections::HashMap;
+
+/// Where the asset index is published
+pub const INDEX_URL: &str = "https://example.com/assets/segment0000/segment0001/segment0002/segment0003/segment0004/segment0005/segment0006/segment0007/segment0008/segment0009/segment0010/segment0011/segment0012/segment0013/segment0014/segment0015/segment0016/segment0017/segment0018/segment0019/segment0020/segment0021/segment0022/segment0023/segment0024/segment0025/segment0026/segment0027/segment0028/segment0029/segment0030/segment0031/segment0032/segment0033/segment0034/segment0035/segment0036/segment0037/segment0038/segment0039/segment0040/segment0041/segment0042/segment0043/segment0044/segment0045/segment0046/segment0047/segment0048/segment0049/segment0050/segment0051/segment0052/segment0053/segment0054/segment0055/segment0056/segment0057/segment0058/segment0059/segment0060/segment0061/segment0062/segment0063/segment0064/segment0065/segment0066/segment0067/segment0068/segment0069/segment0070/segment0071/segment0072/segment0073/segment0074/segment0075/segment0076/segment0077/segment0078/segment0079/segment0080/segment0081/segment0082/segment0083/segment0084/segment0085/segment0086/segment0087/segment0088/segment0089/segment0090/segment0091/segment0092/segment0093/segment0094/segment0095/segment0096/segment0097/segment0098/segment0099/segment0100/segment0101/segment0102/segment0103/segment0104/segment0105/segment0106/segment0107/segment0108/segment0109/segment0110/segment0111/segment0112/segment0113/segment0114/segment0115/segment0116/segment0117/segment0118/segment0119/segment0120/segment0121/segment0122/segment0123/segment0124/segment0125/segment0126/segment0127/segment0128/segment0129/segment0130/segment0131/segment0132/segment0133/segment0134/segment0135/segment0136/segment0137/segment0138/segment0139/segment0140/segment0141/segment0142/segment0143/segment0144/segment0145/segment0146/segment0147/segment0148/segment0149/segment0150/segment0151/segment0152/segment0153/segment0154/segment0155/segment0156/segment0157/segment0158/segment0159/segment0160/segment0161/segment0162/segment0163/segment0164/segment0165/segment0166/segment0167/segment0168/segment0169/segment0170/segment0171/segment0172/segment0173/segment0174/segment0175/segment0176/segment0177/segment0178/segment0179/segment0180/segment0181/segment0182/segment0183/segment0184/segment0185/segment0186/segment0187/segment0188/segment0189/segment0190/segment0191/segment0192/segment0193/segment0194/segment0195/segment0196/segment0197/segment0198/segment0199/segment0200/segment0201/segment0202/segment0203/segment0204/segment0205/segment0206/segment0207/segment0208/segment0209/segment0210/segment0211/segment0212/segment0213/segment0214/segment0215/segment0216/segment0217/segment0218/segment0219/segment0220/segment0221/segment0222/segment0223/segment0224/segment0225/segment0226/segment0227/segment0228/segment0229/segment0230/segment0231/segment0232/segment0233/segment0234/segment0235/segment0236/segment0237/segment0238/segment0239/segment0240/segment0241/segment0242/segment0243/segment0244/segment0245/segment0246/segment0247/segment0248/segment0249/segment0250/segment0251/segment0252/segment0253/segment0254/segment0255/segment0256/segment0257/segment0258/segment0259/segment0260/segment0261/segment0262/segment0263/segment0264/segment0265/segment0266/segment0267/segment0268/segment0269/segment0270/segment0271/segment0272/segment0273/segment0274/segment0275/segment0276/segment0277/segment0278/segment0279/segment0280/segment0281/segment0282/segment0283/segment0284/segment0285/segment0286/segment0287/segment0288/segment0289/segment0290/segment0291/segment0292/segment0293/segment0294/segment0295/segment0296/segment0297/segment0298/segment0299/segment0300/segment0301/segment0302/segment0303/segment0304/segment0305/segment0306/segment0307/segment0308/segment0309/segment0310/segment0311/segment0312/segment0313/segment0314/segment0315/segment0316/segment0317/segment0318/segment0319/segment0320/segment0321/segment0322/segment0323/segment0324/segment0325/segment0326/segment0327/segment0328/segment0329/segment0330/segment0331/segment0332/segment0333/segment0334/segment0335/segment0336/segment0337/segment0338/segment0339/segment0340/segment0341/segment0342/segment0343/segment0344/segment0345/segment0346/segment0347/segment0348/segment0349/segment0350/segment0351/segment0352/segment0353/segment0354/segment0355/segment0356/segment0357/segment0358/segment0359/segment0360/segment0361/segment0362/segment0363/segment0364/segment0365/segment0366/segment0367/segment0368/segment0369/segment0370/segment0371/segment0372/segment0373/segment0374/segment0375/segment0376/segment0377/segment0378/segment0379/segment0380/segment0381/segment0382/segment0383/segment0384/segment0385/segment0386/segment0387/segment0388/segment0389/segment0390/segment0391/segment0392/segment0393/segment0394/segment0395/segment0396/segment0397/segment0398/segment0399/segment0400/segment0401/segment0402/segment0403/segment0404/segment0405/segment0406/segment0407/segment0408/segment0409/segment0410/segment0411/segment0412/segment0413/segment0414/segment0415/segment0416/segment0417/segment0418/segment0419/segment0420/segment0421/segment0422/segment0423/segment0424/segment0425/segment0426/segment0427/segment0428/segment0429/segment0430/segment0431/segment0432/segment0433/segment0434/segment0435/segment0436/segment0437/segment0438/segment0439/segment0440/segment0441/segment0442/segment0443/segment0444/segment0445/segment0446/segment0447/segment0448/segment0449/segment0450/segment0451/segment0452/segment0453/segment0454/segment0455/segment0456/segment0457/segment0458/segment0459/segment0460/segment0461/segment0462/segment0463/segment0464/segment0465/segment0466/segment0467/segment0468/segment0469/segment0470/segment0471/segment0472/segment0473/segment0474/segment0475/segment0476/segment0477/segment0478/segment0479/segment0480/segment0481/segment0482/segment0483/segment0484/segment0485/segment0486/segment0487/segment0488/segment0489/segment0490/segment0491/segment0492/segment0493/segment0494/segment0495/segment0496/segment0497/segment0498/segment0499/segment0500/segment0501/segment0502/segment0503/segment0504/segment0505/segment0506/segment0507/segment0508/segment0509/segment0510/segment0511/segment0512/segment0513/segment0514/segment0515/segment0516/segment0517/segment0518/segment0519.png";
+
+/// Looks up an asset by name
+pub fn asset_url(assets: &HashMap<String, String>, name: &str) -> Option<String> {
+    assets.get(name).map(|path| format!("{}#{}", INDEX_URL, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_assets_have_no_url() {
+        assert_eq!(asset_url(&HashMap::new(), "logo"), None);
+    }
+}
@@ -0,0 +1,33 @@
+//! Minified bundles lose their contents by default; source with one long line keeps them
+
+mod common;
+
+use common::Fixture;
+
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture.write("web/bundle.js", include_str!("fixtures/bundle.js"));
+    fixture.write("src/long_url.rs", include_str!("fixtures/long_url.rs"));
+    fixture
+}
+
+#[test]
+fn bundles_are_skipped_and_long_urls_kept() {
+    let fixture = fixture();
+    fixture.run_ok(&["--stats"]);
+    let document = fixture.document();
+    assert!(document.contains("[generated/minified file skipped, 18374 bytes]"), "bundle contents included");
+    assert!(!document.contains("module-42-"));
+    assert!(document.contains("pub const INDEX_URL: &str = \"https://example.com/assets/segment0000/"));
+    assert!(document.contains("| Generated/minified files | 1 |"));
+    assert_eq!(document.matches("\"classification\": \"minified\"").count(), 1);
+}
+
+#[test]
+fn include_generated_keeps_bundles() {
+    let fixture = fixture();
+    fixture.run_ok(&["--include-generated"]);
+    let document = fixture.document();
+    assert!(document.contains("module-42-"));
+    assert!(!document.contains("generated/minified file skipped"));
+}
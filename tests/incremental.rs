@@ -0,0 +1,115 @@
+//! --incremental: spliced documents match a full rebuild, and stale state falls back to one
+
+mod common;
+
+use std::fs;
+
+use common::{stderr, stdout, Fixture};
+
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture.write("README.md", "# Demo\n");
+    fixture.write("src/a.rs", "fn a() {}\n");
+    fixture.write("src/b.rs", "fn b() {}\n");
+    fixture.write("src/c.rs", "fn c() {}\n");
+    fixture
+}
+
+/// Runs with --incremental and returns the document, asserting it equals a full rebuild with the same options
+fn incremental_run(fixture: &Fixture, args: &[&str]) -> (String, String) {
+    let mut incremental_args = vec!["--incremental", "-v"];
+    incremental_args.extend(args);
+    let output = fixture.run_ok(&incremental_args);
+    let spliced = fixture.document();
+    let log = format!("{}{}", stdout(&output), stderr(&output));
+
+    // A full rebuild into a separate scratch directory must produce the same document
+    let full = Fixture::new();
+    fs::remove_dir(full.path()).unwrap();
+    copy_dir(&fixture.path(), &full.path());
+    full.run_ok(args);
+    assert_eq!(spliced, full.document(), "spliced document differs from a full rebuild");
+    (spliced, log)
+}
+
+fn copy_dir(from: &std::path::Path, to: &std::path::Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()));
+        } else {
+            fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+        }
+    }
+}
+
+#[test]
+fn unchanged_reruns_reuse_every_section() {
+    let fixture = fixture();
+    let (first, log) = incremental_run(&fixture, &["--toc", "--stats"]);
+    assert!(log.contains("Full rebuild: no manifest from a previous run"), "{}", log);
+    assert!(fixture.output_path("repo.md.manifest.json").is_file());
+
+    let (second, log) = incremental_run(&fixture, &["--toc", "--stats"]);
+    assert_eq!(first, second);
+    assert!(log.contains("Reused 4 of 4 file sections from the previous output"), "{}", log);
+}
+
+#[test]
+fn added_modified_and_deleted_files_are_spliced() {
+    let fixture = fixture();
+    incremental_run(&fixture, &["--toc", "--stats"]);
+
+    fixture.write("src/b.rs", "fn b() {\n    println!(\"changed\");\n}\n");
+    fixture.write("src/aa.rs", "fn aa() {}\n");
+    fs::remove_file(fixture.path().join("src/c.rs")).unwrap();
+    let (document, log) = incremental_run(&fixture, &["--toc", "--stats"]);
+
+    assert!(log.contains("Reused 2 of 4 file sections from the previous output"), "{}", log);
+    assert!(document.contains("println!(\"changed\")"));
+    assert!(document.contains("## src/aa.rs"));
+    assert!(document.contains("- [src/aa.rs](#file-src-aa-rs)"));
+    assert!(!document.contains("src/c.rs"));
+}
+
+#[test]
+fn edited_outputs_are_rebuilt() {
+    let fixture = fixture();
+    incremental_run(&fixture, &[]);
+    let path = fixture.output_path("repo.md");
+    fs::write(&path, fixture.document().replace("fn a() {}", "fn edited() {}")).unwrap();
+
+    let (document, log) = incremental_run(&fixture, &[]);
+    assert!(log.contains("Full rebuild: the previous output was modified"), "{}", log);
+    assert!(document.contains("fn a() {}"));
+}
+
+#[test]
+fn other_manifest_versions_are_rebuilt() {
+    let fixture = fixture();
+    incremental_run(&fixture, &[]);
+    let path = fixture.output_path("repo.md.manifest.json");
+    let manifest = fs::read_to_string(&path).unwrap().replacen("\"version\":1,", "\"version\":99,", 1);
+    fs::write(&path, manifest).unwrap();
+
+    let (_, log) = incremental_run(&fixture, &[]);
+    assert!(log.contains("Full rebuild: the manifest has version 99 (expected 1)"), "{}", log);
+
+    fs::write(&path, "{ not json").unwrap();
+    let (_, log) = incremental_run(&fixture, &[]);
+    assert!(log.contains("Full rebuild: the manifest is unreadable"), "{}", log);
+}
+
+#[test]
+fn changed_options_are_rebuilt_but_neutral_ones_are_not() {
+    let fixture = fixture();
+    incremental_run(&fixture, &[]);
+
+    let (_, log) = incremental_run(&fixture, &["--stats"]);
+    assert!(log.contains("Full rebuild: the options differ from the previous run"), "{}", log);
+
+    let report = fixture.root().join("report.json");
+    let (_, log) = incremental_run(&fixture, &["--stats", "--strict", "--report", report.to_str().unwrap()]);
+    assert!(log.contains("Reused 4 of 4 file sections"), "{}", log);
+}
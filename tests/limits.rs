@@ -0,0 +1,75 @@
+//! Per-pattern limits (--limit, [limits] in --config) and their fallback to --max-lines
+
+mod common;
+
+use std::fs;
+
+use common::Fixture;
+
+fn lines(prefix: &str, count: usize) -> String {
+    (1..=count).map(|n| format!("{} {}\n", prefix, n)).collect()
+}
+
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture.write("data/big.json", format!("[{}]\n", "1,".repeat(2000)));
+    fixture.write("data/small.json", "[1]\n");
+    fixture.write("snap/view.snap", lines("snap", 60));
+    fixture.write("src/long.rs", lines("long", 30));
+    fixture.write("src/short.rs", lines("short", 5));
+    fixture
+}
+
+#[test]
+fn matching_rules_apply_and_other_files_fall_back_to_max_lines() {
+    let fixture = fixture();
+    fixture.run_ok(&["--limit", "**/*.json=1K", "--limit", "**/*.snap=100lines", "--max-lines", "10"]);
+    let document = fixture.document();
+
+    assert!(document.contains("[File omitted: 4003 bytes (limit **/*.json=1K)]"), "{}", document);
+    assert!(document.contains("```json\n[1]\n"));
+    // The matching rule allows 100 lines, so --max-lines doesn't apply to the snapshot
+    assert!(document.contains("snap 60\n"));
+    // Files matching no rule fall back to --max-lines
+    assert!(!document.contains("long 1\n"));
+    assert!(document.contains("```rust\nshort 1\n"));
+}
+
+#[test]
+fn the_first_matching_rule_wins() {
+    let fixture = fixture();
+    fixture.run_ok(&["--limit", "data/**=10K", "--limit", "**/*.json=1K"]);
+    assert!(!fixture.document().contains("[File omitted"));
+
+    fixture.run_ok(&["--limit", "**/*.json=1K", "--limit", "data/**=10K"]);
+    assert!(fixture.document().contains("(limit **/*.json=1K)"));
+}
+
+#[test]
+fn config_file_limits_follow_the_command_line_rules() {
+    let fixture = fixture();
+    let config = fixture.root().join("concat.toml");
+    fs::write(&config, "[limits]\n\"**/*.json\" = \"1K\"\n\"**/*.snap\" = \"50lines\"\n").unwrap();
+    let config = config.to_str().unwrap();
+
+    fixture.run_ok(&["--config", config]);
+    let document = fixture.document();
+    assert!(document.contains("(limit **/*.json=1K)"));
+    assert!(document.contains("[File omitted: 60 lines (limit **/*.snap=50lines)]"), "{}", document);
+
+    fixture.run_ok(&["--config", config, "--limit", "**/*.snap=100lines"]);
+    assert!(fixture.document().contains("snap 60\n"));
+
+    fs::write(fixture.root().join("concat.toml"), "[limit]\n").unwrap();
+    let output = fixture.run(&["--config", config]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn huge_preview_line_counts_keep_files_whole() {
+    let fixture = fixture();
+    fixture.run_ok(&["--preview-lines", &usize::MAX.to_string()]);
+    let document = fixture.document();
+    assert!(document.contains("snap 1\n") && document.contains("snap 60\n"));
+    assert!(!document.contains("lines omitted"));
+}
@@ -0,0 +1,79 @@
+//! Line endings of text contents: LF by default, --preserve-eol and the --normalize-eol modes
+
+mod common;
+
+use common::Fixture;
+
+const CRLF: &[u8] = b"fn main() {\r\n    println!(\"hi\");\r\n}\r\n";
+const MIXED: &[u8] = b"first\r\nsecond\nthird\rstill third\r\nlast";
+
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    fixture.write("crlf.rs", CRLF);
+    fixture.write("mixed.txt", MIXED);
+    fixture.write("lf.txt", "plain\nlines\n");
+    fixture
+}
+
+/// Whether `haystack` contains `needle` as a byte sequence
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[test]
+fn crlf_becomes_lf_by_default() {
+    let fixture = fixture();
+    fixture.run_ok(&["--stats"]);
+    let document = fixture.output("repo.md");
+
+    assert!(contains(&document, b"```rust\nfn main() {\n    println!(\"hi\");\n}\n\n```"));
+    // Lone CRs (classic Mac line endings) are left alone
+    assert!(contains(&document, b"first\nsecond\nthird\rstill third\nlast"));
+    assert!(contains(&document, b"plain\nlines\n"));
+    assert!(!contains(&document, b"\r\n"));
+    assert!(contains(&document, b"| Line endings normalized | 2 |"));
+}
+
+#[test]
+fn preserve_eol_keeps_contents_byte_for_byte() {
+    for args in [&["--preserve-eol"][..], &["--normalize-eol", "keep"][..]] {
+        let fixture = fixture();
+        fixture.run_ok(args);
+        let document = fixture.output("repo.md");
+        assert!(contains(&document, CRLF), "{:?}", args);
+        assert!(contains(&document, MIXED), "{:?}", args);
+        assert!(contains(&document, b"plain\nlines\n"));
+    }
+}
+
+#[test]
+fn normalize_eol_crlf_converts_bare_lf() {
+    let fixture = fixture();
+    fixture.run_ok(&["--normalize-eol", "crlf", "--format", "jsonl"]);
+    let records = String::from_utf8(fixture.output("repo.jsonl")).unwrap();
+    let contents: Vec<serde_json::Value> = records.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let content = |path: &str| contents.iter().find(|record| record["path"] == path).unwrap()["content"].as_str().unwrap().to_string();
+
+    assert_eq!(content("crlf.rs").as_bytes(), CRLF);
+    assert_eq!(content("mixed.txt"), "first\r\nsecond\r\nthird\rstill third\r\nlast");
+    assert_eq!(content("lf.txt"), "plain\r\nlines\r\n");
+    assert_eq!(contents.last().unwrap()["normalized"], 2);
+}
+
+#[test]
+fn normalize_eol_lf_is_the_default() {
+    let explicit = fixture();
+    explicit.run_ok(&["--normalize-eol", "lf"]);
+    let default = fixture();
+    default.run_ok(&[]);
+    assert_eq!(explicit.output("repo.md"), default.output("repo.md"));
+}
+
+#[test]
+fn preserve_eol_conflicts_with_converting_modes() {
+    let fixture = fixture();
+    for style in ["lf", "crlf"] {
+        let output = fixture.run(&["--preserve-eol", "--normalize-eol", style]);
+        assert_eq!(output.status.code(), Some(2));
+    }
+}
@@ -0,0 +1,41 @@
+//! --max-depth cuts the structure off below a depth counted from the root or from the selected --path
+
+mod common;
+
+use common::Fixture;
+
+fn fixture() -> Fixture {
+    Fixture::git_repo(&[
+        ("top.rs", "fn top() {}\n"),
+        ("sub/one.rs", "fn one() {}\n"),
+        ("sub/deep/two.rs", "fn two() {}\n"),
+        ("sub/deep/er/three.rs", "fn three() {}\n"),
+    ])
+}
+
+#[test]
+fn depth_is_counted_from_the_root() {
+    let fixture = fixture();
+    fixture.run_ok(&["--max-depth", "1"]);
+    let document = fixture.document();
+    assert!(document.contains("\"truncated\": true"), "{}", document);
+    assert!(document.contains("\"file_count\": 3"), "{}", document);
+    assert!(document.contains("## top.rs") && !document.contains("## sub/one.rs"));
+}
+
+#[test]
+fn depth_is_counted_from_the_selected_path() {
+    let fixture = fixture();
+    fixture.run_ok(&["--path", "sub", "--max-depth", "1"]);
+    let document = fixture.document();
+    // `sub` itself is shown; only `sub/deep` is cut off
+    assert!(document.contains("## sub/one.rs"), "{}", document);
+    assert!(!document.contains("## sub/deep/two.rs") && !document.contains("## top.rs"));
+    assert_eq!(document.matches("\"truncated\": true").count(), 1, "{}", document);
+    assert!(document.contains("\"file_count\": 2"), "{}", document);
+
+    fixture.run_ok(&["--path", "sub/deep", "--max-depth", "1"]);
+    let document = fixture.document();
+    assert!(document.contains("## sub/deep/two.rs"), "{}", document);
+    assert!(!document.contains("## sub/deep/er/three.rs") && !document.contains("## sub/one.rs"));
+}
@@ -0,0 +1,32 @@
+//! Notebook conversion as seen in the generated document
+
+mod common;
+
+use common::{stderr, Fixture};
+
+#[test]
+fn notebooks_are_converted_and_malformed_ones_included_raw() {
+    let fixture = Fixture::new();
+    fixture.write("analysis.ipynb", include_str!("fixtures/notebook.ipynb"));
+    fixture.write("broken.ipynb", "{\"cells\": [\n");
+
+    let output = fixture.run_ok(&[]);
+    assert!(stderr(&output).contains("Including broken.ipynb unconverted"), "{}", stderr(&output));
+
+    let document = fixture.document();
+    assert!(document.contains("````markdown\nJupyter notebook, kernel: Python 3 (ipykernel) (python)\n"));
+    assert!(document.contains("\n```python\nimport pandas as pd\n"));
+    assert!(document.contains("[output omitted: 2 outputs, 1 image]\n"));
+    assert!(!document.contains("iVBORw0KGgo"));
+    // The malformed notebook keeps its raw JSON in the usual fence
+    assert!(document.contains("## broken.ipynb\n\n```\n{\"cells\": [\n\n```"), "{}", document);
+}
+
+#[test]
+fn other_files_keep_the_usual_fence() {
+    let fixture = Fixture::new();
+    fixture.write("README.md", "Run:\n\n```sh\nmake\n```\n");
+
+    fixture.run_ok(&[]);
+    assert!(fixture.document().contains("```markdown\nRun:\n\n```sh\nmake\n```\n\n```"));
+}
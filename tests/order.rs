@@ -0,0 +1,40 @@
+//! --order priority reorders the file sections, not the structure
+
+mod common;
+
+use common::Fixture;
+
+/// Paths of the file sections (`## path` headings), in document order
+fn sections(document: &str) -> Vec<&str> {
+    document.lines().filter_map(|line| line.strip_prefix("## ")).collect()
+}
+
+#[test]
+fn priority_order_puts_readme_docs_and_manifests_first() {
+    let fixture = Fixture::git_repo(&[
+        ("Cargo.toml", "[package]\n"),
+        ("README.md", "# Project\n"),
+        ("assets/data.csv", "a,b\n"),
+        ("docs/guide.md", "guide\n"),
+        ("src/lib.rs", "pub fn lib() {}\n"),
+        ("src/main.rs", "fn main() {}\n"),
+        ("tests/cli.rs", "#[test]\nfn cli() {}\n"),
+    ]);
+
+    fixture.run_ok(&[]);
+    let by_path = fixture.document();
+    assert_eq!(
+        sections(&by_path),
+        ["Cargo.toml", "README.md", "assets/data.csv", "docs/guide.md", "src/lib.rs", "src/main.rs", "tests/cli.rs"]
+    );
+
+    fixture.run_ok(&["--order", "priority"]);
+    let by_priority = fixture.document();
+    assert_eq!(
+        sections(&by_priority),
+        ["README.md", "docs/guide.md", "Cargo.toml", "src/lib.rs", "src/main.rs", "tests/cli.rs", "assets/data.csv"]
+    );
+    // The structure is the same in both
+    let structure = |document: &str| document.split("# File Contents").next().unwrap().to_string();
+    assert_eq!(structure(&by_path), structure(&by_priority));
+}
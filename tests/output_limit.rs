@@ -0,0 +1,22 @@
+//! --on-limit truncate stops at the first file over the limit and leaves the rest out of --stats
+
+mod common;
+
+use common::Fixture;
+
+#[test]
+fn truncation_stops_at_the_first_file_over_the_limit() {
+    let fixture = Fixture::new();
+    fixture.write("a.txt", "a\n");
+    fixture.write("b.txt", "x".repeat(3000));
+    // Small enough to fit after b.txt is dropped, but still left out
+    fixture.write("c.txt", "c\n");
+    fixture.run_ok(&["--max-output-size", "2500", "--on-limit", "truncate", "--stats"]);
+    let document = fixture.document();
+
+    assert!(document.contains("## a.txt"));
+    assert!(!document.contains("## b.txt") && !document.contains("## c.txt"), "{}", document);
+    assert!(document.contains("- b.txt\n- c.txt\n"), "{}", document);
+    assert!(document.contains("| Total files | 1 |"), "{}", document);
+    assert!(document.contains("| other | 1 | 2 | 1 |"), "{}", document);
+}
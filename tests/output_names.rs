@@ -0,0 +1,61 @@
+//! Output file names: --timestamp never overwrites an earlier run, --no-clobber refuses to
+
+mod common;
+
+use std::fs;
+
+use common::{stderr, Fixture};
+
+/// Names of the files in the output directory, sorted
+fn output_names(fixture: &Fixture) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(fixture.root().join("output"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Whether `name` is `repo-<YYYYMMDDTHHMMSS>` followed by an optional `-<n>` and the extension
+fn is_timestamped(name: &str, extension: &str) -> bool {
+    let Some(stem) = name.strip_prefix("repo-").and_then(|name| name.strip_suffix(extension)) else {
+        return false;
+    };
+    let (timestamp, number) = stem.split_at(stem.len().min(15));
+    let digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+    timestamp.len() == 15
+        && digits(&timestamp[..8])
+        && &timestamp[8..9] == "T"
+        && digits(&timestamp[9..])
+        && (number.is_empty() || number.strip_prefix('-').is_some_and(digits))
+}
+
+#[test]
+fn timestamped_runs_keep_every_output() {
+    let fixture = Fixture::git_repo(&[("main.rs", "fn main() {}\n")]);
+    for _ in 0..3 {
+        fixture.run_ok(&["--timestamp"]);
+    }
+    fixture.run_ok(&["--timestamp", "--no-clobber", "--format", "text"]);
+
+    let names = output_names(&fixture);
+    assert_eq!(names.len(), 4, "{:?}", names);
+    assert_eq!(names.iter().filter(|name| is_timestamped(name, ".md")).count(), 3, "{:?}", names);
+    assert!(names.iter().any(|name| is_timestamped(name, ".txt")), "{:?}", names);
+}
+
+#[test]
+fn no_clobber_leaves_existing_outputs_alone() {
+    let fixture = Fixture::git_repo(&[("main.rs", "fn main() {}\n")]);
+    fixture.run_ok(&[]);
+    fs::write(fixture.output_path("repo.md"), "earlier output").unwrap();
+
+    let output = fixture.run(&["--no-clobber"]);
+    assert_eq!(output.status.code(), Some(5), "{}", stderr(&output));
+    assert!(stderr(&output).contains("not overwritten with --no-clobber"), "{}", stderr(&output));
+    assert_eq!(fixture.output("repo.md"), b"earlier output");
+
+    // Without --no-clobber the file is replaced
+    fixture.run_ok(&[]);
+    assert!(fixture.document().contains("fn main() {}"));
+}
@@ -0,0 +1,72 @@
+//! Files and directories without read permission are reported and the walk goes on; --strict stops at them
+
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use common::{stderr, Fixture};
+
+/// Sets the permission bits of a path
+fn chmod(path: &Path, mode: u32) {
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+/// A repository with an unreadable file in `src/` and an unreadable directory `locked/`, or None when
+/// permissions aren't enforced (e.g. when running as root)
+fn fixture() -> Option<Fixture> {
+    let fixture = Fixture::git_repo(&[("src/main.rs", "fn main() {}\n"), ("src/private.rs", "secret\n"), ("locked/inner.rs", "hidden\n")]);
+    chmod(&fixture.path().join("src/private.rs"), 0o000);
+    chmod(&fixture.path().join("locked"), 0o000);
+    if fs::read(fixture.path().join("src/private.rs")).is_ok() {
+        eprintln!("skipped: permissions are not enforced for this user");
+        // Leave the scratch directory removable
+        chmod(&fixture.path().join("locked"), 0o755);
+        return None;
+    }
+    Some(fixture)
+}
+
+/// Makes everything readable again so the scratch directory can be cleaned up
+fn unlock(fixture: &Fixture) {
+    chmod(&fixture.path().join("locked"), 0o755);
+    chmod(&fixture.path().join("src/private.rs"), 0o644);
+}
+
+#[test]
+fn unreadable_entries_are_reported_and_skipped() {
+    let Some(fixture) = fixture() else { return };
+    let output = fixture.run(&[]);
+    let stderr = stderr(&output);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr);
+    assert!(stderr.contains("Skipping unreadable locked: Permission denied"), "{}", stderr);
+    assert!(stderr.contains("2 files unreadable"), "{}", stderr);
+
+    let document = fixture.document();
+    assert!(document.contains("fn main() {}"));
+    assert!(document.contains("## src/private.rs"));
+    assert!(document.contains("[File unreadable: Permission denied"), "{}", document);
+    assert!(document.contains("\"error\": \"Permission denied"), "{}", document);
+    assert!(!document.contains("inner.rs") && !document.contains("secret"));
+    unlock(&fixture);
+}
+
+#[test]
+fn strict_runs_stop_at_unreadable_entries() {
+    let Some(fixture) = fixture() else { return };
+    // A directory the walk can't list aborts the run
+    let output = fixture.run(&["--strict"]);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    assert!(stderr(&output).contains("Permission denied"));
+    assert!(!fixture.output_path("repo.md").exists());
+
+    // An unreadable file only fails the run after the document is written
+    chmod(&fixture.path().join("locked"), 0o755);
+    let output = fixture.run(&["--strict-on", "unreadable"]);
+    assert_eq!(output.status.code(), Some(8), "{}", stderr(&output));
+    assert!(fixture.document().contains("[File unreadable: Permission denied"));
+    unlock(&fixture);
+}
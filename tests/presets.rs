@@ -0,0 +1,85 @@
+//! What each --preset keeps, how --include overrides it, and what --show-ignores prints
+
+mod common;
+
+use common::{stdout, Fixture};
+
+/// A repository with a bit of everything the presets tell apart
+fn fixture() -> Fixture {
+    let long: String = (0..600).map(|n| format!("// line {}\n", n)).collect();
+    Fixture::git_repo(&[
+        ("README.md", "# Project\n"),
+        ("LICENSE", "MIT\n"),
+        ("CMakeLists.txt", "project(demo)\n"),
+        ("Cargo.toml", "[package]\n"),
+        ("Cargo.lock", "# lock\n"),
+        ("docs/guide.md", "guide\n"),
+        ("docs/notes.txt", "notes\n"),
+        ("assets/logo.svg", "<svg/>\n"),
+        (".github/workflows/ci.yml", "on: push\n"),
+        ("src/main.rs", "fn main() {}\n"),
+        ("src/long.rs", &long),
+        ("src/__snapshots__/view.snap", "snapshot\n"),
+        ("tests/cli.rs", "#[test]\nfn cli() {}\n"),
+        ("examples/demo.rs", "fn main() {}\n"),
+    ])
+}
+
+/// Paths of the file sections of the document
+fn sections(fixture: &Fixture) -> Vec<String> {
+    fixture.document().lines().filter_map(|line| line.strip_prefix("## ")).map(String::from).collect()
+}
+
+#[test]
+fn code_only_drops_docs_media_lockfiles_ci_and_fixtures() {
+    let fixture = fixture();
+    fixture.run_ok(&["--preset", "code-only"]);
+    assert_eq!(sections(&fixture), ["CMakeLists.txt", "Cargo.toml", "examples/demo.rs", "src/long.rs", "src/main.rs", "tests/cli.rs"]);
+}
+
+#[test]
+fn docs_only_keeps_markdown_and_root_manifests() {
+    let fixture = fixture();
+    fixture.run_ok(&["--preset", "docs-only"]);
+    assert_eq!(sections(&fixture), ["Cargo.toml", "README.md", "docs/guide.md"]);
+}
+
+#[test]
+fn minimal_also_drops_tests_and_long_files() {
+    let fixture = fixture();
+    fixture.run_ok(&["--preset", "minimal"]);
+    let document = fixture.document();
+    assert_eq!(sections(&fixture), ["CMakeLists.txt", "Cargo.toml", "src/long.rs", "src/main.rs"]);
+    assert!(!document.contains("// line 599"), "files over 500 lines keep their contents");
+    // An explicit --max-lines replaces the preset's limit
+    fixture.run_ok(&["--preset", "minimal", "--max-lines", "1000"]);
+    assert!(fixture.document().contains("// line 599"));
+}
+
+#[test]
+fn include_and_exclude_override_presets() {
+    let fixture = fixture();
+    fixture.run_ok(&["--preset", "code-only", "--include", "README.md", "--exclude", "examples/"]);
+    let sections = sections(&fixture);
+    assert!(sections.iter().any(|path| path == "README.md"), "{:?}", sections);
+    assert!(!sections.iter().any(|path| path.starts_with("examples/")), "{:?}", sections);
+}
+
+#[test]
+fn show_ignores_prints_every_rule_source() {
+    let fixture = fixture();
+    fixture.write(".concatignore", "# comment\n*.log\n");
+    let output = fixture.run_ok(&["--show-ignores", "--preset", "minimal", "--exclude", "vendor/", "--include", "!keep.log"]);
+    let text = stdout(&output);
+    assert!(text.starts_with("Effective ignore rules (later rules take precedence):"), "{}", text);
+    assert!(text.contains("Built-in directories: "), "{}", text);
+    assert!(text.contains(".env* files: excluded (--include-env keeps them)"), "{}", text);
+    assert!(text.contains("Preset minimal (code-only, also dropping tests"), "{}", text);
+    assert!(text.contains("\n  Cargo.lock\n") && text.contains("\n  *_test.*\n"), "{}", text);
+    assert!(text.contains(".concatignore:\n  *.log\n"), "{}", text);
+    assert!(!text.contains("# comment"), "{}", text);
+    assert!(text.contains("--exclude/--include:\n  vendor/\n"), "{}", text);
+    assert!(text.contains("Files with more than 500 lines are omitted (--max-lines)."), "{}", text);
+    // Nothing is generated
+    assert!(!fixture.root().join("output").exists());
+}
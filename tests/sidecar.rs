@@ -0,0 +1,77 @@
+//! Byte ranges in the --manifest sidecar slice the document into exactly its file sections
+
+mod common;
+
+use std::fs;
+
+use common::Fixture;
+use serde_json::Value;
+
+/// A repository with multi-byte text, a binary file and files that sort differently by priority
+fn fixture() -> Fixture {
+    let fixture = Fixture::git_repo(&[
+        ("README.md", "# Überblick 📦\n"),
+        ("src/main.rs", "fn main() {\n    println!(\"héllo wörld\");\n}\n"),
+        ("src/日本.txt", "こんにちは\n"),
+        ("tests/cli.rs", "#[test]\nfn cli() {}\n"),
+    ]);
+    fixture.write("data.bin", b"\0\x01\x02binary");
+    fixture.commit("binary");
+    fixture
+}
+
+/// Runs with --manifest and checks every range against the document, returning the sliced paths in order.
+/// Each section starts with `marker` (only once) and contains the `heading` of its path.
+fn slices(fixture: &Fixture, args: &[&str], output: &str, marker: &str, heading: impl Fn(&str) -> String) -> Vec<String> {
+    let manifest = fixture.root().join("manifest.json");
+    let manifest_arg = manifest.to_string_lossy().into_owned();
+    let mut args = args.to_vec();
+    args.extend(["--manifest", manifest_arg.as_str()]);
+    fixture.run_ok(&args);
+
+    let document = fixture.output(output);
+    let sidecar: Value = serde_json::from_str(&fs::read_to_string(&manifest).unwrap()).unwrap();
+    let files = sidecar["files"].as_array().unwrap();
+    assert_eq!(sidecar["total_files"].as_u64(), Some(files.len() as u64));
+
+    let skipped = sidecar["skipped"].as_array().unwrap();
+    assert!(skipped.iter().any(|file| file["path"] == "data.bin" && file["reason"] == "binary"), "{:?}", skipped);
+
+    let mut end = None;
+    let mut paths = Vec::new();
+    for file in files {
+        let path = file["path"].as_str().unwrap();
+        let (offset, length) = (file["offset"].as_u64().unwrap() as usize, file["length"].as_u64().unwrap() as usize);
+        // Sections are byte ranges that follow each other without gaps
+        if let Some(end) = end {
+            assert_eq!(offset, end, "gap before {}", path);
+        }
+        end = Some(offset + length);
+        let section = std::str::from_utf8(&document[offset..offset + length]).unwrap_or_else(|_| panic!("{} is not split at a character", path));
+        assert!(section.starts_with(marker), "{}: {:?}", path, section);
+        assert_eq!(section.matches(marker).count(), 1, "{} spans more than one section", path);
+        assert!(section.contains(&heading(path)), "{}: {:?}", path, section);
+        // Skipped files only have a placeholder
+        if !skipped.iter().any(|file| file["path"] == path) {
+            let contents = fs::read_to_string(fixture.path().join(path)).unwrap();
+            assert!(section.contains(contents.trim_end()), "{}: {:?}", path, section);
+        }
+        paths.push(path.to_string());
+    }
+    assert_eq!(end, Some(document.len()), "the last section ends the document");
+    paths
+}
+
+#[test]
+fn markdown_sections_slice_exactly() {
+    let fixture = fixture();
+    let paths = slices(&fixture, &["--toc"], "repo.md", "<a id=\"file-", |path| format!("\n## {}\n", path));
+    assert_eq!(paths, ["README.md", "data.bin", "src/main.rs", "src/日本.txt", "tests/cli.rs"]);
+}
+
+#[test]
+fn text_sections_slice_exactly_in_priority_order() {
+    let fixture = fixture();
+    let paths = slices(&fixture, &["--format", "text", "--order", "priority"], "repo.txt", "===== ", |path| format!("===== {} =====", path));
+    assert_eq!(paths, ["README.md", "src/main.rs", "tests/cli.rs", "data.bin", "src/日本.txt"]);
+}
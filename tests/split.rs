@@ -0,0 +1,45 @@
+//! --split writes parts of at most the given size, or refuses when the structure alone is larger
+
+mod common;
+
+use std::fs;
+
+use common::{stderr, Fixture};
+
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    for n in 1..=9 {
+        fixture.write(&format!("src/module{}.rs", n), format!("pub fn f{}() {{\n{}}}\n", n, "    // line\n".repeat(8)));
+    }
+    fixture
+}
+
+#[test]
+fn structures_larger_than_a_part_are_rejected() {
+    let fixture = fixture();
+    let output = fixture.run(&["--split", "1000"]);
+    let stderr = stderr(&output);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr);
+    assert!(stderr.contains("more than the --split size of 1000 bytes"), "{}", stderr);
+    assert!(stderr.contains("--no-structure"), "{}", stderr);
+    assert!(!fixture.output_path("repo.part1.md").exists());
+}
+
+#[test]
+fn every_part_fits_the_size() {
+    let fixture = fixture();
+    fixture.run_ok(&["--split", "1000", "--no-structure"]);
+    let parts: Vec<String> = (1..)
+        .map(|n| fixture.output_path(&format!("repo.part{}.md", n)))
+        .take_while(|path| path.exists())
+        .map(|path| fs::read_to_string(path).unwrap())
+        .collect();
+    assert!(parts.len() > 1);
+    for part in &parts {
+        assert!(part.len() <= 1000, "{} bytes", part.len());
+    }
+    let all = parts.concat();
+    for n in 1..=9 {
+        assert_eq!(all.matches(&format!("## src/module{}.rs", n)).count(), 1);
+    }
+}
@@ -0,0 +1,38 @@
+//! --top lists the largest directories and files, largest first, counting only included files
+
+mod common;
+
+use common::{stderr, Fixture};
+
+#[test]
+fn top_orders_by_size_then_path() {
+    let kb = |n: usize| "x".repeat(n * 1024);
+    let fixture = Fixture::git_repo(&[
+        ("big/one.txt", &kb(20)),
+        ("big/nested/two.txt", &kb(10)),
+        ("big/nested/skipped.log", &kb(100)),
+        ("mid/three.txt", &kb(12)),
+        ("tie-b/four.txt", &kb(2)),
+        ("tie-a/five.txt", &kb(2)),
+        ("root.txt", &kb(1)),
+    ]);
+    let output = fixture.run_ok(&["--top", "3", "--exclude", "*.log"]);
+    let stderr = stderr(&output);
+    let lines: Vec<&str> = stderr.lines().map(str::trim).collect();
+
+    let dirs = lines.iter().position(|line| *line == "Largest directories:").expect("no directory list");
+    let files = lines.iter().position(|line| *line == "Largest files:").expect("no file list");
+    let listed = |range: &[&str]| range.iter().map(|line| line.split_whitespace().last().unwrap().to_string()).collect::<Vec<_>>();
+    assert_eq!(listed(&lines[dirs + 1..files]), ["big/", "mid/", "big/nested/"], "{}", stderr);
+    assert_eq!(listed(&lines[files + 1..files + 4]), ["big/one.txt", "mid/three.txt", "big/nested/two.txt"], "{}", stderr);
+    // The excluded log file counts neither towards the size nor the file count of its directories
+    assert!(lines[dirs + 1].starts_with("30 KB") && lines[dirs + 1].contains("2 files"), "{}", stderr);
+    assert!(lines[dirs + 3].starts_with("10 KB") && lines[dirs + 3].contains(" 1 file "), "{}", stderr);
+
+    // Equal sizes are listed by path
+    let output = fixture.run_ok(&["--top", "10", "--exclude", "*.log"]);
+    let stderr = common::stderr(&output);
+    let tie_a = stderr.find("tie-a/\n").unwrap();
+    let tie_b = stderr.find("tie-b/\n").unwrap();
+    assert!(tie_a < tie_b, "{}", stderr);
+}
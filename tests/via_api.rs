@@ -0,0 +1,23 @@
+//! --via-api without curl falls back to cloning instead of failing outright
+
+mod common;
+
+use assert_cmd::Command;
+use common::stderr;
+
+#[test]
+fn missing_curl_falls_back_to_cloning() {
+    let root = tempfile::tempdir().unwrap();
+    let empty_path = tempfile::tempdir().unwrap();
+    // Neither curl nor git can be found, so nothing reaches the network
+    let output = Command::cargo_bin("repo-to-ai-context-docs")
+        .unwrap()
+        .current_dir(root.path())
+        .args(["https://github.com/octo/hello", "--via-api", "--no-cache"])
+        .env("PATH", empty_path.path())
+        .output()
+        .unwrap();
+    let stderr = stderr(&output);
+    assert!(stderr.contains("GitHub API request failed (curl is not installed), cloning instead"), "{}", stderr);
+    assert_eq!(output.status.code(), Some(4), "{}", stderr);
+}
@@ -0,0 +1,68 @@
+//! The structure walk runs on several threads; its output and report must not depend on their timing
+
+mod common;
+
+use std::fs;
+
+use common::Fixture;
+
+/// A wide and deep tree: 12 top-level directories, each four levels deep with files at every level
+fn fixture() -> Fixture {
+    let fixture = Fixture::new();
+    for top in 0..12 {
+        let mut dir = format!("d{:02}", top);
+        for level in 0..4 {
+            for file in 0..3 {
+                fixture.write(&format!("{}/f{}.txt", dir, file), format!("{} {}\n", dir, file));
+            }
+            dir = format!("{}/l{}", dir, level);
+        }
+    }
+    fixture
+}
+
+#[cfg(unix)]
+fn lock(fixture: &Fixture, dirs: &[&str], mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+    for dir in dirs {
+        fs::set_permissions(fixture.path().join(dir), fs::Permissions::from_mode(mode)).unwrap();
+    }
+}
+
+#[test]
+fn repeated_runs_are_byte_identical() {
+    let fixture = fixture();
+    let locked = ["d03/l0", "d07/l0/l1/l2", "d11"];
+    #[cfg(unix)]
+    {
+        lock(&fixture, &locked, 0o000);
+        // Broken symlinks are reported too
+        std::os::unix::fs::symlink("missing", fixture.path().join("d05/l0/dangling.txt")).unwrap();
+        std::os::unix::fs::symlink("missing", fixture.path().join("d01/dangling.txt")).unwrap();
+    }
+    let enforced = fs::read_dir(fixture.path().join(locked[0])).is_err();
+
+    let mut runs = Vec::new();
+    for _ in 0..6 {
+        fixture.run_ok(&["--report", "report.json"]);
+        let report = fs::read_to_string(fixture.root().join("report.json")).unwrap();
+        runs.push((fixture.document(), report));
+    }
+    let (document, report) = &runs[0];
+    for run in &runs[1..] {
+        assert_eq!(&run.0, document);
+        assert_eq!(&run.1, report);
+    }
+
+    assert!(document.contains("## d00/l0/l1/l2/f2.txt"), "{}", document);
+    if enforced {
+        let positions: Vec<usize> = locked.iter().map(|dir| report.find(&format!("\"{}\"", dir)).expect(dir)).collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]), "{}", report);
+        assert!(!document.contains("## d11/f0.txt"));
+    }
+    #[cfg(unix)]
+    {
+        assert!(report.contains("d01/dangling.txt") && report.contains("d05/l0/dangling.txt"), "{}", report);
+        lock(&fixture, &locked, 0o755);
+    }
+}